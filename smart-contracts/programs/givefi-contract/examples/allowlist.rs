@@ -0,0 +1,21 @@
+//! Builds an allowlist tree from a CSV of members and prints the root plus every member's proof.
+//!
+//!     cargo run --example allowlist -- members.csv
+
+use givefi_contract::allowlist::AllowlistTree;
+
+fn hex(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args().nth(1).ok_or("usage: allowlist <members.csv>")?;
+    let tree = AllowlistTree::from_csv(&std::fs::read_to_string(path)?)?;
+
+    println!("root {}", hex(&tree.root()));
+    for (index, member) in tree.members().iter().enumerate() {
+        let proof: Vec<String> = tree.proof(index).iter().map(hex).collect();
+        println!("{} allowance={} proof=[{}]", member.user, member.allowance, proof.join(","));
+    }
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! Merkle allowlists for member-only giveaways.
+//!
+//! Leaves are `hash(0x00 || pubkey || allowance_le)` and inner nodes hash their two children in
+//! sorted order behind a `0x01` prefix, so proofs carry no left/right flags. An allowance of 0
+//! leaves the member limited only by the giveaway's own caps.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+pub fn leaf(user: &Pubkey, allowance: u64) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, user.as_ref(), &allowance.to_le_bytes()]).to_bytes()
+}
+
+fn parent(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+pub fn verify(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    proof.iter().fold(leaf, |node, sibling| parent(&node, sibling)) == *root
+}
+
+#[cfg(not(target_os = "solana"))]
+pub use builder::*;
+
+// Off-chain tree building, used by creators to publish a root and hand proofs to members
+#[cfg(not(target_os = "solana"))]
+mod builder {
+    use super::*;
+    use std::collections::HashSet;
+    use std::fmt;
+    use std::str::FromStr;
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct AllowlistMember {
+        pub user: Pubkey,
+        pub allowance: u64,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum AllowlistError {
+        Empty,
+        InvalidPubkey { line: usize, value: String },
+        InvalidAllowance { line: usize, value: String },
+        DuplicateMember(Pubkey),
+    }
+
+    impl fmt::Display for AllowlistError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                AllowlistError::Empty => write!(f, "allowlist has no members"),
+                AllowlistError::InvalidPubkey { line, value } => write!(f, "line {}: invalid pubkey {:?}", line, value),
+                AllowlistError::InvalidAllowance { line, value } => {
+                    write!(f, "line {}: invalid ticket allowance {:?}", line, value)
+                },
+                AllowlistError::DuplicateMember(user) => write!(f, "{} is listed more than once", user),
+            }
+        }
+    }
+
+    impl std::error::Error for AllowlistError {}
+
+    pub struct AllowlistTree {
+        members: Vec<AllowlistMember>,
+        // layers[0] holds the leaves, the last layer holds only the root
+        layers: Vec<Vec<[u8; 32]>>,
+    }
+
+    impl AllowlistTree {
+        pub fn new(members: Vec<AllowlistMember>) -> std::result::Result<Self, AllowlistError> {
+            if members.is_empty() {
+                return Err(AllowlistError::Empty);
+            }
+            let mut seen = HashSet::new();
+            if let Some(duplicate) = members.iter().find(|member| !seen.insert(member.user)) {
+                return Err(AllowlistError::DuplicateMember(duplicate.user));
+            }
+
+            let mut layers = vec![members.iter().map(|member| leaf(&member.user, member.allowance)).collect::<Vec<_>>()];
+            while layers.last().unwrap().len() > 1 {
+                // A node without a sibling is carried up unchanged
+                let next = layers
+                    .last()
+                    .unwrap()
+                    .chunks(2)
+                    .map(|pair| match pair {
+                        [left, right] => parent(left, right),
+                        [single] => *single,
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                layers.push(next);
+            }
+
+            Ok(Self { members, layers })
+        }
+
+        // One member per line as `pubkey[,allowance]`. Blank lines, `#` comments and a leading
+        // `pubkey` header are skipped; a missing allowance means 0.
+        pub fn from_csv(csv: &str) -> std::result::Result<Self, AllowlistError> {
+            let mut members = Vec::new();
+            for (index, raw_line) in csv.lines().enumerate() {
+                let line = index + 1;
+                let raw_line = raw_line.trim();
+                if raw_line.is_empty() || raw_line.starts_with('#') {
+                    continue;
+                }
+
+                let mut fields = raw_line.split(',').map(str::trim);
+                let user_field = fields.next().unwrap_or_default();
+                if members.is_empty() && user_field.eq_ignore_ascii_case("pubkey") {
+                    continue;
+                }
+                let user = Pubkey::from_str(user_field).map_err(|_| AllowlistError::InvalidPubkey {
+                    line,
+                    value: user_field.to_string(),
+                })?;
+                let allowance = match fields.next().filter(|field| !field.is_empty()) {
+                    Some(field) => field.parse().map_err(|_| AllowlistError::InvalidAllowance {
+                        line,
+                        value: field.to_string(),
+                    })?,
+                    None => 0,
+                };
+                members.push(AllowlistMember { user, allowance });
+            }
+
+            Self::new(members)
+        }
+
+        pub fn root(&self) -> [u8; 32] {
+            self.layers.last().unwrap()[0]
+        }
+
+        pub fn members(&self) -> &[AllowlistMember] {
+            &self.members
+        }
+
+        pub fn proof(&self, index: usize) -> Vec<[u8; 32]> {
+            let mut proof = Vec::new();
+            let mut position = index;
+            for layer in &self.layers[..self.layers.len() - 1] {
+                if let Some(sibling) = layer.get(position ^ 1) {
+                    proof.push(*sibling);
+                }
+                position /= 2;
+            }
+            proof
+        }
+
+        // The member's allowance and proof, ready to pass as an `AllowlistProof`
+        pub fn proof_for(&self, user: &Pubkey) -> Option<(u64, Vec<[u8; 32]>)> {
+            let index = self.members.iter().position(|member| member.user == *user)?;
+            Some((self.members[index].allowance, self.proof(index)))
+        }
+    }
+}
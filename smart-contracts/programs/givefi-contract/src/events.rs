@@ -0,0 +1,287 @@
+//! Typed events for indexers and notifications.
+//!
+//! Every instruction publishes through `emit_cpi!`, which records the event as a self-CPI in the
+//! transaction's inner instructions, so it survives the log truncation that `msg!` output hits.
+//! Token amounts are in base units of their mint; a `None` mint means lamports.
+
+use anchor_lang::prelude::*;
+
+use crate::{ClaimDeadline, GiveawayStatus, PaymentType, ProposalKind, ProposalStatus};
+
+#[event]
+pub struct ProgramInitialized {
+    pub authority: Pubkey,
+    pub treasury_wallet: Pubkey,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct TreasuryWalletChanged {
+    pub previous_treasury: Pubkey,
+    pub treasury_wallet: Pubkey,
+}
+
+#[event]
+pub struct PauseFlagsChanged {
+    pub paused: u8,
+}
+
+#[event]
+pub struct JackpotLimitsChanged {
+    pub max_treasury_fee_bps: u16,
+    pub min_winner_share_bps: u16,
+}
+
+#[event]
+pub struct OracleProgramChanged {
+    pub oracle_program: Pubkey,
+}
+
+#[event]
+pub struct CrankBountyChanged {
+    pub crank_bounty_lamports: u64,
+}
+
+#[event]
+pub struct DaoConfigured {
+    pub members: Vec<Pubkey>,
+    pub quorum: u8,
+    pub voting_period_secs: i64,
+    pub approval_required: bool,
+}
+
+#[event]
+pub struct GiveawayFreezeChanged {
+    pub giveaway_id: u64,
+    pub frozen: bool,
+}
+
+#[event]
+pub struct GiveawayCreated {
+    pub giveaway_id: u64,
+    pub authority: Pubkey,
+    pub entry_cost_sol: u64,
+    pub entry_cost_give: Option<u64>,
+    pub max_entries: u64,
+    pub min_participants: u64,
+    pub max_entries_per_wallet: Option<u64>,
+    pub end_timestamp: i64,
+    pub total_winners: u64,
+    pub prize_mint: Option<Pubkey>,
+    pub prize_amount: u64,
+    pub claim_deadline: Option<ClaimDeadline>,
+    pub status: GiveawayStatus,
+}
+
+#[event]
+pub struct GiveawayStatusChanged {
+    pub giveaway_id: u64,
+    pub from: GiveawayStatus,
+    pub to: GiveawayStatus,
+}
+
+#[event]
+pub struct PrizeFunded {
+    pub giveaway_id: u64,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EntryMintAdded {
+    pub giveaway_id: u64,
+    pub mint: Pubkey,
+    pub price: u64,
+}
+
+#[event]
+pub struct GiveawayProposed {
+    pub giveaway_id: u64,
+    pub proposer: Pubkey,
+    pub kind: ProposalKind,
+    pub voting_ends_at: i64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub giveaway_id: u64,
+    pub voter: Pubkey,
+    pub approve: bool,
+    pub votes_for: u8,
+    pub votes_against: u8,
+    pub status: ProposalStatus,
+}
+
+#[event]
+pub struct EntryPurchased {
+    pub giveaway_id: u64,
+    pub user: Pubkey,
+    pub first_entry_number: u64,
+    pub tickets: u64,
+    pub bonus_tickets: u64,
+    pub payment_type: PaymentType,
+    pub amount_paid: u64,
+    pub total_entries: u64,
+}
+
+#[event]
+pub struct RandomnessRequested {
+    pub giveaway_id: u64,
+    pub oracle_program: Pubkey,
+    pub seed: [u8; 32],
+    pub slot: u64,
+    // Slot whose hash a reveal will mix in
+    pub draw_slot: u64,
+}
+
+#[event]
+pub struct DrawSlotPinned {
+    pub giveaway_id: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DrawSlotRecorded {
+    pub giveaway_id: u64,
+    pub slot: u64,
+    pub slot_hash: [u8; 32],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DrawSource {
+    Oracle,
+    Reveal,
+    Fallback,
+}
+
+#[event]
+pub struct WinnerDrawn {
+    pub giveaway_id: u64,
+    pub winners: Vec<u64>,
+    pub source: DrawSource,
+    pub commitment_slashed: bool,
+}
+
+#[event]
+pub struct PrizeClaimed {
+    pub giveaway_id: u64,
+    pub user: Pubkey,
+    pub first_entry_number: u64,
+    pub tiers: Vec<u8>,
+    pub prize_mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+// One per currency the pool was collected in
+#[event]
+pub struct JackpotPaid {
+    pub giveaway_id: u64,
+    pub user: Pubkey,
+    pub first_entry_number: u64,
+    pub mint: Option<Pubkey>,
+    pub winner_amount: u64,
+    pub creator_amount: u64,
+    pub treasury_amount: u64,
+}
+
+#[event]
+pub struct PrizeReclaimed {
+    pub giveaway_id: u64,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct GiveawayCancelled {
+    pub giveaway_id: u64,
+    pub canceller: Pubkey,
+    pub prize_returned: u64,
+}
+
+#[event]
+pub struct RefundPaid {
+    pub giveaway_id: u64,
+    pub user: Pubkey,
+    pub first_entry_number: u64,
+    pub payment_type: PaymentType,
+    pub amount: u64,
+    // Set when the refund was pushed by crank_refunds
+    pub cranker: Option<Pubkey>,
+}
+
+// A forfeited winner's positions wait for the hash of `slot`; no forfeited entry means the
+// platform re-pinned a slot whose hash aged out
+#[event]
+pub struct RedrawQueued {
+    pub giveaway_id: u64,
+    pub forfeited_entry_number: Option<u64>,
+    pub positions: Vec<u8>,
+    pub slot: u64,
+}
+
+#[event]
+pub struct WinnersRedrawn {
+    pub giveaway_id: u64,
+    pub positions: Vec<u8>,
+    // New winning entry numbers, one per position
+    pub replacements: Vec<u64>,
+    pub claim_deadline: i64,
+}
+
+// One per escrowed prize or pool a forfeited share was routed out of
+#[event]
+pub struct UnclaimedRouted {
+    pub giveaway_id: u64,
+    pub first_entry_number: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    // Set for the escrowed prize, clear for jackpot pools
+    pub prize: bool,
+    pub amount: u64,
+}
+
+// One per vault reconcile_vault moved anything out of
+#[event]
+pub struct VaultReconciled {
+    pub giveaway_id: u64,
+    pub mint: Option<Pubkey>,
+    pub treasury_wallet: Pubkey,
+    // Rounding dust of a settled giveaway, booked as a fee
+    pub dust: u64,
+    // What arrived that the ledger never accounted for
+    pub surplus: u64,
+    // Rent reserve returned to the creator once the vault owes nothing more; part of creator_amount
+    pub reserve: u64,
+    // Split of the pool slices no jackpot claim took; the creator's amount includes the winners' part
+    pub creator_amount: u64,
+    pub treasury_amount: u64,
+}
+
+#[event]
+pub struct EntryClosed {
+    pub giveaway_id: u64,
+    pub user: Pubkey,
+    pub first_entry_number: u64,
+}
+
+// A bookkeeping account was closed and its rent returned
+#[event]
+pub struct RentReclaimed {
+    // 0 for accounts that don't belong to one giveaway
+    pub giveaway_id: u64,
+    pub account: Pubkey,
+    pub recipient: Pubkey,
+    pub lamports: u64,
+}
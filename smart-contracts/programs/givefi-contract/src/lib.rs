@@ -1,8 +1,62 @@
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::hash::{hash, hashv};
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    transfer_fee::TransferFeeConfig, transfer_hook::TransferHook, BaseStateWithExtensions, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+pub mod allowlist;
+pub mod events;
+pub mod math;
+pub mod views;
+
+pub use events::*;
+pub use views::*;
 
 declare_id!("48mihemhp1UxYjz1UznH4fJ9FnF3AfN3XG18GasPFamU");
 
+// Time after end_timestamp the authority has to reveal its committed seed
+pub const REVEAL_WINDOW_SECS: i64 = 3600;
+
+// Draws mix in the hash of a slot this far past the one that pins it, so nobody knows the hash
+// when the draw is committed to
+pub const DRAW_SLOT_DELAY: u64 = 10;
+
+// Oracle programs sign fulfilment CPIs with the PDA derived from this seed
+pub const ORACLE_AUTHORITY_SEED: &[u8] = b"oracle_authority";
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const MAX_PRIZE_TIERS: usize = 5;
+pub const MAX_WINNERS: usize = 20;
+// Winning entries a Redraw giveaway can close while drawn; once full, redraws fall back to routing
+pub const MAX_RETIRED_ENTRIES: usize = 2 * MAX_WINNERS;
+pub const MAX_TIER_DESCRIPTION_LEN: usize = 32;
+
+// Historical 33/60/7 winner/creator/treasury split, used when a giveaway doesn't set its own
+pub const DEFAULT_JACKPOT_SPLIT: JackpotSplit = JackpotSplit { winner_bps: 3_300, creator_bps: 6_000, treasury_bps: 700 };
+pub const DEFAULT_MAX_TREASURY_FEE_BPS: u16 = 700;
+pub const DEFAULT_MIN_WINNER_SHARE_BPS: u16 = 3_300;
+
+pub const MAX_DAO_MEMBERS: usize = 10;
+
+// SPL mints (USDC, USDT, ...) a giveaway can take entries in, on top of SOL and GIVE
+pub const MAX_ACCEPTED_MINTS: usize = 4;
+
+// ProgramState.paused bits; refunds are never pausable
+pub const PAUSE_ENTRIES: u8 = 1 << 0;
+pub const PAUSE_DRAWS: u8 = 1 << 1;
+pub const PAUSE_CLAIMS: u8 = 1 << 2;
+pub const PAUSE_CREATION: u8 = 1 << 3;
+
+// Ids allocated from ProgramState.total_giveaways start here, so they never collide with ids
+// chosen by callers, which must stay below it
+pub const AUTO_GIVEAWAY_ID_BASE: u64 = 1 << 63;
+
 #[program]
 pub mod givefi {
     use super::*;
@@ -12,65 +66,542 @@ pub mod givefi {
         program_state.authority = ctx.accounts.authority.key();
         program_state.treasury_wallet = treasury_wallet;
         program_state.total_giveaways = 0;
+        program_state.oracle_program = Pubkey::default();
+        program_state.max_treasury_fee_bps = DEFAULT_MAX_TREASURY_FEE_BPS;
+        program_state.min_winner_share_bps = DEFAULT_MIN_WINNER_SHARE_BPS;
+        program_state.dao_approval_required = false;
+        program_state.pending_authority = None;
+        program_state.config_changes = 0;
+        program_state.paused = 0;
+        program_state.crank_bounty_lamports = 0;
         program_state.bump = ctx.bumps.program_state;
+
+        emit_cpi!(ProgramInitialized { authority: program_state.authority, treasury_wallet });
+        Ok(())
+    }
+
+    // Two-step handover: the new authority must sign accept_authority, so a mistyped key
+    // can never take control. Proposing None cancels a pending handover.
+    pub fn propose_authority(ctx: Context<UpdateProgramState>, new_authority: Option<Pubkey>) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.pending_authority = new_authority;
+        program_state.config_changes = math::add(program_state.config_changes, 1)?;
+
+        msg!("Pending authority set to {:?}", new_authority);
+        emit_cpi!(AuthorityProposed { authority: program_state.authority, pending_authority: new_authority });
+        Ok(())
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+        let previous_authority = program_state.authority;
+        program_state.authority = ctx.accounts.new_authority.key();
+        program_state.pending_authority = None;
+        program_state.config_changes = math::add(program_state.config_changes, 1)?;
+
+        msg!("Authority transferred from {} to {}", previous_authority, program_state.authority);
+        emit_cpi!(AuthorityTransferred { previous_authority, new_authority: program_state.authority });
+        Ok(())
+    }
+
+    pub fn set_treasury_wallet(ctx: Context<UpdateProgramState>, treasury_wallet: Pubkey) -> Result<()> {
+        require!(treasury_wallet != Pubkey::default(), GivefiError::InvalidTreasuryWallet);
+
+        let program_state = &mut ctx.accounts.program_state;
+        let previous_treasury = program_state.treasury_wallet;
+        program_state.treasury_wallet = treasury_wallet;
+        program_state.config_changes = math::add(program_state.config_changes, 1)?;
+
+        msg!("Treasury wallet changed from {} to {}", previous_treasury, treasury_wallet);
+        emit_cpi!(TreasuryWalletChanged { previous_treasury, treasury_wallet });
+        Ok(())
+    }
+
+    pub fn set_paused(ctx: Context<UpdateProgramState>, paused: u8) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.paused = paused;
+        program_state.config_changes = math::add(program_state.config_changes, 1)?;
+
+        msg!("Pause flags set to {:#06b}", paused);
+        emit_cpi!(PauseFlagsChanged { paused });
+        Ok(())
+    }
+
+    pub fn freeze_giveaway(ctx: Context<FreezeGiveaway>) -> Result<()> {
+        let giveaway = &mut ctx.accounts.giveaway;
+        giveaway.frozen = true;
+
+        msg!("Giveaway {} frozen", giveaway.id);
+        emit_cpi!(GiveawayFreezeChanged { giveaway_id: giveaway.id, frozen: true });
+        Ok(())
+    }
+
+    pub fn unfreeze_giveaway(ctx: Context<FreezeGiveaway>) -> Result<()> {
+        let giveaway = &mut ctx.accounts.giveaway;
+        giveaway.frozen = false;
+
+        msg!("Giveaway {} unfrozen", giveaway.id);
+        emit_cpi!(GiveawayFreezeChanged { giveaway_id: giveaway.id, frozen: false });
+        Ok(())
+    }
+
+    pub fn set_jackpot_split_limits(
+        ctx: Context<UpdateProgramState>,
+        max_treasury_fee_bps: u16,
+        min_winner_share_bps: u16,
+    ) -> Result<()> {
+        require!(max_treasury_fee_bps as u64 <= BPS_DENOMINATOR, GivefiError::InvalidJackpotSplit);
+        require!(min_winner_share_bps as u64 <= BPS_DENOMINATOR, GivefiError::InvalidJackpotSplit);
+
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.max_treasury_fee_bps = max_treasury_fee_bps;
+        program_state.min_winner_share_bps = min_winner_share_bps;
+        program_state.config_changes = math::add(program_state.config_changes, 1)?;
+
+        msg!("Jackpot limits set: treasury <= {} bps, winner >= {} bps", max_treasury_fee_bps, min_winner_share_bps);
+        emit_cpi!(JackpotLimitsChanged { max_treasury_fee_bps, min_winner_share_bps });
+        Ok(())
+    }
+
+    pub fn set_oracle_program(ctx: Context<UpdateProgramState>, oracle_program: Pubkey) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.oracle_program = oracle_program;
+        program_state.config_changes = math::add(program_state.config_changes, 1)?;
+
+        msg!("Oracle program set to {}", oracle_program);
+        emit_cpi!(OracleProgramChanged { oracle_program });
+        Ok(())
+    }
+
+    pub fn set_crank_bounty(ctx: Context<UpdateProgramState>, crank_bounty_lamports: u64) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.crank_bounty_lamports = crank_bounty_lamports;
+        program_state.config_changes = math::add(program_state.config_changes, 1)?;
+
+        msg!("Crank bounty set to {} lamports", crank_bounty_lamports);
+        emit_cpi!(CrankBountyChanged { crank_bounty_lamports });
         Ok(())
     }
 
     pub fn create_giveaway(
         ctx: Context<CreateGiveaway>,
-        giveaway_id: u64,
+        // None takes the next id from the ProgramState counter
+        giveaway_id: Option<u64>,
         entry_cost_sol: u64,
         entry_cost_give: Option<u64>,
         max_entries: u64,
         min_participants: u64,
+        max_entries_per_wallet: Option<u64>,
+        allowlist_root: Option<[u8; 32]>,
+        entry_gate: Option<EntryGate>,
         prize_description: String,
         end_timestamp: i64,
         jackpot_option_enabled: bool,
         early_end_enabled: bool,
+        randomness_commitment: [u8; 32],
+        prize_tiers: Vec<PrizeTier>,
+        jackpot_split: Option<JackpotSplit>,
+        prize_mint: Option<Pubkey>,
+        prize_amount: u64,
+        claim_deadline: Option<ClaimDeadline>,
     ) -> Result<()> {
         let giveaway = &mut ctx.accounts.giveaway;
         let clock = Clock::get()?;
 
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_CREATION), GivefiError::ProgramPaused);
+        require!(giveaway_id.is_none_or(|id| id < AUTO_GIVEAWAY_ID_BASE), GivefiError::ReservedGiveawayId);
         require!(end_timestamp > clock.unix_timestamp, GivefiError::InvalidEndTime);
         require!(entry_cost_sol > 0, GivefiError::InvalidEntryPrice);
         require!(max_entries > 0, GivefiError::InvalidMaxEntries);
         require!(min_participants > 0 && min_participants <= max_entries, GivefiError::InvalidMinParticipants);
+        require!(
+            max_entries_per_wallet.is_none_or(|cap| cap > 0 && cap <= max_entries),
+            GivefiError::InvalidWalletEntryCap
+        );
         require!(prize_description.len() <= 100, GivefiError::DescriptionTooLong);
+        require!(randomness_commitment != [0u8; 32], GivefiError::InvalidCommitment);
+        if let Some(deadline) = &claim_deadline {
+            require!(deadline.window_secs > 0, GivefiError::InvalidClaimDeadline);
+            if let UnclaimedPolicy::Charity { wallet } = deadline.policy {
+                require!(wallet != Pubkey::default(), GivefiError::InvalidClaimDeadline);
+            }
+        }
+        // SOL-only giveaways skip the GIVE vault and its rent entirely
+        if entry_cost_give.is_some() {
+            require!(ctx.accounts.giveaway_token_vault.is_some(), GivefiError::GiveAccountsMissing);
+            let give_mint = ctx.accounts.give_mint.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+            require!(!has_transfer_hook(&give_mint.to_account_info())?, GivefiError::TransferHookNotSupported);
+        } else {
+            require!(ctx.accounts.giveaway_token_vault.is_none(), GivefiError::GiveVaultNotNeeded);
+        }
+
+        // No tiers means the classic single-winner giveaway
+        let prize_tiers = if prize_tiers.is_empty() {
+            vec![PrizeTier { count: 1, share_bps: BPS_DENOMINATOR as u16, description: String::new() }]
+        } else {
+            prize_tiers
+        };
+        validate_prize_tiers(&prize_tiers, min_participants)?;
+
+        let jackpot_split = jackpot_split.unwrap_or(DEFAULT_JACKPOT_SPLIT);
+        jackpot_split.validate(&ctx.accounts.program_state)?;
 
-        giveaway.id = giveaway_id;
+        // Every winner must receive a non-zero slice of an escrowed prize (an NFT is amount 1)
+        if prize_mint.is_some() {
+            for tier in &prize_tiers {
+                require!(tier_slice(prize_amount, tier)? > 0, GivefiError::InvalidPrizeAmount);
+            }
+        }
+
+        giveaway.id = ctx.accounts.program_state.giveaway_id_for(giveaway_id)?;
         giveaway.authority = ctx.accounts.authority.key();
         giveaway.entry_cost_sol = entry_cost_sol;
         giveaway.entry_cost_give = entry_cost_give;
         giveaway.max_entries = max_entries;
         giveaway.min_participants = min_participants;
+        giveaway.max_entries_per_wallet = max_entries_per_wallet;
+        giveaway.allowlist_root = allowlist_root;
+        giveaway.entry_gate = entry_gate;
         giveaway.current_entries = 0;
         giveaway.sol_entries = 0;
         giveaway.give_entries = 0;
+        giveaway.give_pool = 0;
+        giveaway.accepted_mints = Vec::new();
+        giveaway.sol_ledger = VaultLedger::default();
+        giveaway.give_ledger = VaultLedger::default();
         giveaway.prize_description = prize_description;
+        giveaway.prize_mint = prize_mint;
+        giveaway.prize_amount = if prize_mint.is_some() { prize_amount } else { 0 };
+        // Giveaways without an on-chain prize or DAO review are open immediately
+        giveaway.prize_funded = prize_mint.is_none();
+        giveaway.dao_approved = !ctx.accounts.program_state.dao_approval_required;
+        giveaway.status = if giveaway.prize_funded && giveaway.dao_approved {
+            GiveawayStatus::Open
+        } else {
+            GiveawayStatus::PendingApproval
+        };
+        giveaway.frozen = false;
         giveaway.end_timestamp = end_timestamp;
         giveaway.jackpot_option_enabled = jackpot_option_enabled;
+        giveaway.jackpot_split = jackpot_split;
         giveaway.early_end_enabled = early_end_enabled;
-        giveaway.is_active = true;
-        giveaway.winner = None;
-        giveaway.prize_claimed = false;
-        giveaway.jackpot_claimed = false;
-        giveaway.is_successful = false;
-        giveaway.randomness_requested = false;
+        giveaway.prize_tiers = prize_tiers;
+        giveaway.winners = Vec::new();
+        giveaway.winner_deadlines = Vec::new();
+        giveaway.prizes_claimed = 0;
+        giveaway.jackpots_claimed = 0;
+        giveaway.forfeited = 0;
+        giveaway.retired_tickets = Vec::new();
+        giveaway.pending_redraws = Vec::new();
+        giveaway.redraw_slot = 0;
+        giveaway.claim_deadline = claim_deadline;
+        giveaway.drawn_at = 0;
+        giveaway.randomness_commitment = randomness_commitment;
+        giveaway.recorded_slot = 0;
+        giveaway.recorded_slot_hash = [0u8; 32];
+        giveaway.oracle_requested = false;
+        giveaway.commitment_slashed = false;
         giveaway.bump = ctx.bumps.giveaway;
 
+        // The vault starts rent-exempt, so no payout can leave it holding a rent-paying balance of
+        // rounding dust; reconcile_vault hands the reserve back at the end
+        let reserve = Rent::get()?.minimum_balance(0);
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: ctx.accounts.giveaway_vault.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, reserve)?;
+        giveaway.sol_ledger.reserve = reserve;
+
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.total_giveaways = math::add(program_state.total_giveaways, 1)?;
+
+        grow_creator_index(
+            &ctx.accounts.creator_index,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+        let creator_index = &mut ctx.accounts.creator_index;
+        creator_index.creator = giveaway.authority;
+        creator_index.giveaway_ids.push(giveaway.id);
+        creator_index.bump = ctx.bumps.creator_index;
+
+        emit_cpi!(GiveawayCreated {
+            giveaway_id: giveaway.id,
+            authority: giveaway.authority,
+            entry_cost_sol,
+            entry_cost_give,
+            max_entries,
+            min_participants,
+            max_entries_per_wallet,
+            end_timestamp,
+            total_winners: giveaway.total_winners() as u64,
+            prize_mint,
+            prize_amount: giveaway.prize_amount,
+            claim_deadline,
+            status: giveaway.status,
+        });
+        Ok(())
+    }
+
+    // Can be sent in the same transaction as create_giveaway to open a funded giveaway atomically
+    pub fn fund_prize(ctx: Context<FundPrize>) -> Result<()> {
+        let giveaway = &mut ctx.accounts.giveaway;
+
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_CREATION), GivefiError::ProgramPaused);
+        require!(!giveaway.prize_funded, GivefiError::PrizeAlreadyFunded);
+        require!(giveaway.status == GiveawayStatus::PendingApproval, GivefiError::GiveawayNotActive);
+        require!(
+            !has_transfer_hook(&ctx.accounts.prize_mint.to_account_info())?,
+            GivefiError::TransferHookNotSupported
+        );
+
+        // Transfer-fee mints deliver less than was sent; winners share what actually arrived
+        giveaway.prize_amount = deposit_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.authority_prize_account,
+            &mut ctx.accounts.prize_vault,
+            &ctx.accounts.prize_mint,
+            &ctx.accounts.authority,
+            giveaway.prize_amount,
+        )?;
+        giveaway.prize_funded = true;
+        let opened = giveaway.open_if_ready()?;
+
+        msg!("Prize escrowed for giveaway {}: {} of {}", giveaway.id, giveaway.prize_amount, ctx.accounts.prize_mint.key());
+        emit_cpi!(PrizeFunded {
+            giveaway_id: giveaway.id,
+            mint: ctx.accounts.prize_mint.key(),
+            amount: giveaway.prize_amount,
+        });
+        if let Some(event) = opened {
+            emit_cpi!(event);
+        }
+        Ok(())
+    }
+
+    // Lets entries be paid in another SPL mint at its own price, with its own vault
+    pub fn add_entry_mint(ctx: Context<AddEntryMint>, price: u64) -> Result<()> {
+        let giveaway = &mut ctx.accounts.giveaway;
+        let mint = ctx.accounts.mint.key();
+
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_CREATION), GivefiError::ProgramPaused);
+        require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
+        require!(
+            matches!(giveaway.status, GiveawayStatus::PendingApproval | GiveawayStatus::Open),
+            GivefiError::GiveawayNotActive
+        );
+        require!(giveaway.current_entries == 0, GivefiError::EntriesAlreadyOpen);
+        require!(price > 0, GivefiError::InvalidEntryPrice);
+        require!(giveaway.accepted_mints.len() < MAX_ACCEPTED_MINTS, GivefiError::TooManyAcceptedMints);
+        require!(
+            giveaway.accepted_mints.iter().all(|accepted| accepted.mint != mint),
+            GivefiError::MintAlreadyAccepted
+        );
+        require!(
+            !has_transfer_hook(&ctx.accounts.mint.to_account_info())?,
+            GivefiError::TransferHookNotSupported
+        );
+
+        giveaway.accepted_mints.push(AcceptedMint { mint, price, entries: 0, pool: 0, ledger: VaultLedger::default() });
+
+        msg!("Giveaway {} accepts {} at {} per ticket", giveaway.id, mint, price);
+        emit_cpi!(EntryMintAdded { giveaway_id: giveaway.id, mint, price });
+        Ok(())
+    }
+
+    pub fn configure_dao(
+        ctx: Context<ConfigureDao>,
+        members: Vec<Pubkey>,
+        quorum: u8,
+        voting_period_secs: i64,
+        approval_required: bool,
+    ) -> Result<()> {
+        require!(members.len() <= MAX_DAO_MEMBERS, GivefiError::TooManyDaoMembers);
+        require!(quorum > 0 && quorum as usize <= members.len(), GivefiError::InvalidQuorum);
+        require!(voting_period_secs > 0, GivefiError::InvalidVotingPeriod);
+
+        let dao_config = &mut ctx.accounts.dao_config;
+        dao_config.members = members;
+        dao_config.quorum = quorum;
+        dao_config.voting_period_secs = voting_period_secs;
+        dao_config.bump = ctx.bumps.dao_config;
+
+        // Only affects giveaways created from now on
         let program_state = &mut ctx.accounts.program_state;
-        program_state.total_giveaways += 1;
+        program_state.dao_approval_required = approval_required;
+        program_state.config_changes = math::add(program_state.config_changes, 1)?;
+
+        msg!("DAO configured: {} members, quorum {}", dao_config.members.len(), quorum);
+        emit_cpi!(DaoConfigured {
+            members: dao_config.members.clone(),
+            quorum,
+            voting_period_secs,
+            approval_required,
+        });
+        Ok(())
+    }
+
+    pub fn propose_giveaway(ctx: Context<ProposeGiveaway>) -> Result<()> {
+        let giveaway = &ctx.accounts.giveaway;
+        let clock = Clock::get()?;
+
+        require!(!giveaway.dao_approved, GivefiError::ProposalNotNeeded);
+        require!(giveaway.status == GiveawayStatus::PendingApproval, GivefiError::GiveawayNotActive);
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.open(
+            giveaway.id,
+            ProposalKind::Approve,
+            ctx.accounts.authority.key(),
+            clock.unix_timestamp,
+            ctx.accounts.dao_config.voting_period_secs,
+        )?;
+        proposal.bump = ctx.bumps.proposal;
+
+        msg!("Giveaway {} submitted for DAO review", giveaway.id);
+        emit_cpi!(GiveawayProposed {
+            giveaway_id: giveaway.id,
+            proposer: proposal.proposer,
+            kind: proposal.kind,
+            voting_ends_at: proposal.voting_ends_at,
+        });
+        Ok(())
+    }
+
+    // The DAO's route to cancel_giveaway: any member opens the vote and a quorum of approvals
+    // cancels the giveaway, under the same rules as a platform cancellation
+    pub fn propose_cancellation(ctx: Context<ProposeCancellation>) -> Result<()> {
+        let giveaway = &ctx.accounts.giveaway;
+        let proposer = ctx.accounts.member.key();
+        let clock = Clock::get()?;
+
+        require!(ctx.accounts.dao_config.members.contains(&proposer), GivefiError::NotDaoMember);
+        require!(giveaway.status.is_live(), GivefiError::GiveawayNotActive);
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.open(
+            giveaway.id,
+            ProposalKind::Cancel,
+            proposer,
+            clock.unix_timestamp,
+            ctx.accounts.dao_config.voting_period_secs,
+        )?;
+        proposal.bump = ctx.bumps.proposal;
+
+        msg!("Cancellation of giveaway {} submitted to the DAO", giveaway.id);
+        emit_cpi!(GiveawayProposed {
+            giveaway_id: giveaway.id,
+            proposer,
+            kind: proposal.kind,
+            voting_ends_at: proposal.voting_ends_at,
+        });
+        Ok(())
+    }
+
+    pub fn cast_vote(ctx: Context<CastVote>, approve: bool) -> Result<()> {
+        let dao_config = &ctx.accounts.dao_config;
+        let proposal = &mut ctx.accounts.proposal;
+        let voter = ctx.accounts.member.key();
+        let clock = Clock::get()?;
+
+        require!(dao_config.members.contains(&voter), GivefiError::NotDaoMember);
+        require!(proposal.status == ProposalStatus::Voting, GivefiError::ProposalNotVoting);
+        require!(clock.unix_timestamp < proposal.voting_ends_at, GivefiError::VotingClosed);
+        require!(!proposal.voters.contains(&voter), GivefiError::AlreadyVoted);
+        // A cancellation can only pass while there is still something to cancel
+        if proposal.kind == ProposalKind::Cancel {
+            require!(ctx.accounts.giveaway.status.is_live(), GivefiError::GiveawayNotActive);
+        }
+
+        proposal.voters.push(voter);
+        if approve {
+            proposal.votes_for = proposal.votes_for.checked_add(1).ok_or(GivefiError::MathOverflow)?;
+        } else {
+            proposal.votes_against = proposal.votes_against.checked_add(1).ok_or(GivefiError::MathOverflow)?;
+        }
 
+        // Settle as soon as the outcome is certain: quorum of approvals reached, or too many
+        // rejections left for the remaining members to reach it
+        let members = dao_config.members.len() as u8;
+        let mut changed = None;
+        let mut cancelled = false;
+        if proposal.votes_for >= dao_config.quorum {
+            proposal.status = ProposalStatus::Approved;
+            let giveaway = &mut ctx.accounts.giveaway;
+            match proposal.kind {
+                ProposalKind::Approve => {
+                    giveaway.dao_approved = true;
+                    changed = giveaway.open_if_ready()?;
+                    msg!("Giveaway {} approved by the DAO", proposal.giveaway_id);
+                }
+                // Any escrowed prize stays put; the creator takes it back through reclaim_prize
+                ProposalKind::Cancel => {
+                    changed = Some(giveaway.transition(GiveawayStatus::Cancelled)?);
+                    cancelled = true;
+                    msg!("Giveaway {} cancelled by the DAO", proposal.giveaway_id);
+                }
+            }
+        } else if members.saturating_sub(proposal.votes_against) < dao_config.quorum {
+            proposal.status = ProposalStatus::Rejected;
+            msg!("DAO proposal for giveaway {} rejected", proposal.giveaway_id);
+        }
+
+        emit_cpi!(VoteCast {
+            giveaway_id: proposal.giveaway_id,
+            voter,
+            approve,
+            votes_for: proposal.votes_for,
+            votes_against: proposal.votes_against,
+            status: proposal.status,
+        });
+        if cancelled {
+            emit_cpi!(GiveawayCancelled {
+                giveaway_id: proposal.giveaway_id,
+                canceller: dao_config.key(),
+                prize_returned: 0,
+            });
+        }
+        if let Some(event) = changed {
+            emit_cpi!(event);
+        }
         Ok(())
     }
 
-    pub fn enter_giveaway_sol(ctx: Context<EnterGiveawaySol>) -> Result<()> {
+    // For collection-gated giveaways, remaining_accounts may carry [token account, metadata,
+    // bonus claim PDA] for each extra NFT the holder wants bonus tickets for
+    pub fn enter_giveaway_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EnterGiveawaySol<'info>>,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
         let giveaway = &mut ctx.accounts.giveaway;
         let clock = Clock::get()?;
 
-        require!(giveaway.is_active, GivefiError::GiveawayNotActive);
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_ENTRIES), GivefiError::ProgramPaused);
+        require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
+        require!(giveaway.prize_funded, GivefiError::PrizeNotFunded);
+        require!(giveaway.dao_approved, GivefiError::AwaitingDaoApproval);
+        require!(giveaway.status == GiveawayStatus::Open, GivefiError::GiveawayNotActive);
         require!(clock.unix_timestamp < giveaway.end_timestamp, GivefiError::GiveawayEnded);
-        require!(giveaway.current_entries < giveaway.max_entries, GivefiError::MaxEntriesReached);
+        let allowance = check_allowlist(giveaway, &ctx.accounts.user.key(), allowlist_proof.as_ref())?;
+        let bonus_tickets = check_entry_gate(
+            giveaway,
+            &ctx.accounts.user,
+            ctx.accounts.gate_token_account.as_ref(),
+            ctx.accounts.gate_metadata.as_ref(),
+            ctx.remaining_accounts,
+            &ctx.accounts.system_program,
+            ctx.program_id,
+        )?;
+        let tickets = math::add(1, bonus_tickets)?;
+        require!(
+            tickets <= giveaway.max_entries.saturating_sub(giveaway.current_entries),
+            GivefiError::MaxEntriesReached
+        );
+        ctx.accounts.participant.add_tickets(giveaway, ctx.accounts.user.key(), ctx.bumps.participant, tickets, allowance)?;
 
         let transfer_instruction = anchor_lang::system_program::Transfer {
             from: ctx.accounts.user.to_account_info(),
@@ -83,56 +614,240 @@ pub mod givefi {
         );
 
         anchor_lang::system_program::transfer(cpi_ctx, giveaway.entry_cost_sol)?;
+        let received = giveaway.entry_cost_sol;
 
         let entry = &mut ctx.accounts.entry;
         entry.giveaway_id = giveaway.id;
         entry.user = ctx.accounts.user.key();
-        entry.entry_number = giveaway.current_entries;
+        entry.payer = ctx.accounts.user.key();
+        entry.first_entry_number = giveaway.current_entries;
+        entry.count = tickets;
+        entry.bonus_tickets = bonus_tickets;
+        entry.amount_paid = received;
         entry.timestamp = clock.unix_timestamp;
         entry.payment_type = PaymentType::Sol;
         entry.claimed = false;
         entry.bump = ctx.bumps.entry;
 
-        giveaway.current_entries += 1;
-        giveaway.sol_entries += 1;
+        giveaway.current_entries = math::add(giveaway.current_entries, tickets)?;
+        giveaway.sol_entries = math::add(giveaway.sol_entries, 1)?;
+        giveaway.sol_ledger.collected = math::add(giveaway.sol_ledger.collected, received)?;
 
+        emit_cpi!(EntryPurchased {
+            giveaway_id: giveaway.id,
+            user: entry.user,
+            first_entry_number: entry.first_entry_number,
+            tickets,
+            bonus_tickets,
+            payment_type: entry.payment_type.clone(),
+            amount_paid: received,
+            total_entries: giveaway.current_entries,
+        });
         Ok(())
     }
 
-    pub fn enter_giveaway_give(ctx: Context<EnterGiveawayGive>) -> Result<()> {
+    pub fn enter_giveaway_give<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EnterGiveawayGive<'info>>,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
         let giveaway = &mut ctx.accounts.giveaway;
         let clock = Clock::get()?;
 
-        require!(giveaway.is_active, GivefiError::GiveawayNotActive);
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_ENTRIES), GivefiError::ProgramPaused);
+        require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
+        require!(giveaway.prize_funded, GivefiError::PrizeNotFunded);
+        require!(giveaway.dao_approved, GivefiError::AwaitingDaoApproval);
+        require!(giveaway.status == GiveawayStatus::Open, GivefiError::GiveawayNotActive);
         require!(clock.unix_timestamp < giveaway.end_timestamp, GivefiError::GiveawayEnded);
-        require!(giveaway.current_entries < giveaway.max_entries, GivefiError::MaxEntriesReached);
+        let allowance = check_allowlist(giveaway, &ctx.accounts.user.key(), allowlist_proof.as_ref())?;
+        let bonus_tickets = check_entry_gate(
+            giveaway,
+            &ctx.accounts.user,
+            ctx.accounts.gate_token_account.as_ref(),
+            ctx.accounts.gate_metadata.as_ref(),
+            ctx.remaining_accounts,
+            &ctx.accounts.system_program,
+            ctx.program_id,
+        )?;
+        let tickets = math::add(1, bonus_tickets)?;
+        require!(
+            tickets <= giveaway.max_entries.saturating_sub(giveaway.current_entries),
+            GivefiError::MaxEntriesReached
+        );
+        ctx.accounts.participant.add_tickets(giveaway, ctx.accounts.user.key(), ctx.bumps.participant, tickets, allowance)?;
         require!(giveaway.entry_cost_give.is_some(), GivefiError::GiveTokensNotAccepted);
 
-        let transfer_instruction = Transfer {
-            from: ctx.accounts.user_token_account.to_account_info(),
-            to: ctx.accounts.giveaway_token_vault.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        };
+        let received = deposit_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.user_token_account,
+            &mut ctx.accounts.giveaway_token_vault,
+            &ctx.accounts.give_mint,
+            &ctx.accounts.user,
+            giveaway.entry_cost_give.unwrap(),
+        )?;
 
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            transfer_instruction,
+        let entry = &mut ctx.accounts.entry;
+        entry.giveaway_id = giveaway.id;
+        entry.user = ctx.accounts.user.key();
+        entry.payer = ctx.accounts.user.key();
+        entry.first_entry_number = giveaway.current_entries;
+        entry.count = tickets;
+        entry.bonus_tickets = bonus_tickets;
+        entry.amount_paid = received;
+        entry.timestamp = clock.unix_timestamp;
+        entry.payment_type = PaymentType::Give;
+        entry.claimed = false;
+        entry.bump = ctx.bumps.entry;
+
+        giveaway.current_entries = math::add(giveaway.current_entries, tickets)?;
+        giveaway.give_entries = math::add(giveaway.give_entries, 1)?;
+        giveaway.give_pool = math::add(giveaway.give_pool, received)?;
+        giveaway.give_ledger.collected = math::add(giveaway.give_ledger.collected, received)?;
+
+        emit_cpi!(EntryPurchased {
+            giveaway_id: giveaway.id,
+            user: entry.user,
+            first_entry_number: entry.first_entry_number,
+            tickets,
+            bonus_tickets,
+            payment_type: entry.payment_type.clone(),
+            amount_paid: received,
+            total_entries: giveaway.current_entries,
+        });
+        Ok(())
+    }
+
+    // Buys `quantity` consecutive tickets with one payment and one entry account
+    pub fn enter_giveaway_bulk<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EnterGiveawayBulk<'info>>,
+        quantity: u64,
+        payment_type: PaymentType,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
+        let giveaway = &mut ctx.accounts.giveaway;
+        let clock = Clock::get()?;
+
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_ENTRIES), GivefiError::ProgramPaused);
+        require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
+        require!(giveaway.prize_funded, GivefiError::PrizeNotFunded);
+        require!(giveaway.dao_approved, GivefiError::AwaitingDaoApproval);
+        require!(giveaway.status == GiveawayStatus::Open, GivefiError::GiveawayNotActive);
+        require!(clock.unix_timestamp < giveaway.end_timestamp, GivefiError::GiveawayEnded);
+        require!(quantity > 0, GivefiError::InvalidTicketQuantity);
+        let allowance = check_allowlist(giveaway, &ctx.accounts.user.key(), allowlist_proof.as_ref())?;
+        let bonus_tickets = check_entry_gate(
+            giveaway,
+            &ctx.accounts.user,
+            ctx.accounts.gate_token_account.as_ref(),
+            ctx.accounts.gate_metadata.as_ref(),
+            ctx.remaining_accounts,
+            &ctx.accounts.system_program,
+            ctx.program_id,
+        )?;
+        let tickets = math::add(quantity, bonus_tickets)?;
+        require!(
+            tickets <= giveaway.max_entries.saturating_sub(giveaway.current_entries),
+            GivefiError::MaxEntriesReached
         );
+        ctx.accounts.participant.add_tickets(giveaway, ctx.accounts.user.key(), ctx.bumps.participant, tickets, allowance)?;
+
+        let received = match payment_type {
+            PaymentType::Sol => {
+                let total_cost = math::mul(giveaway.entry_cost_sol, quantity)?;
+
+                let transfer_instruction = anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.giveaway_vault.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    transfer_instruction,
+                );
+                anchor_lang::system_program::transfer(cpi_ctx, total_cost)?;
+
+                giveaway.sol_entries = math::add(giveaway.sol_entries, quantity)?;
+                giveaway.sol_ledger.collected = math::add(giveaway.sol_ledger.collected, total_cost)?;
+                total_cost
+            },
+            PaymentType::Give => {
+                let entry_cost_give = giveaway.entry_cost_give.ok_or(GivefiError::GiveTokensNotAccepted)?;
+                let total_cost = math::mul(entry_cost_give, quantity)?;
+                let user_token_account = ctx.accounts.user_token_account.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+                let giveaway_token_vault = ctx.accounts.giveaway_token_vault.as_mut().ok_or(GivefiError::GiveAccountsMissing)?;
+                let payment_mint = ctx.accounts.payment_mint.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+                let token_program = ctx.accounts.token_program.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+                require_keys_eq!(user_token_account.mint, giveaway_token_vault.mint, GivefiError::InvalidTokenMint);
+
+                let received = deposit_tokens(
+                    token_program,
+                    user_token_account,
+                    giveaway_token_vault,
+                    payment_mint,
+                    &ctx.accounts.user,
+                    total_cost,
+                )?;
+
+                giveaway.give_entries = math::add(giveaway.give_entries, quantity)?;
+                giveaway.give_pool = math::add(giveaway.give_pool, received)?;
+                giveaway.give_ledger.collected = math::add(giveaway.give_ledger.collected, received)?;
+                received
+            },
+            PaymentType::Spl { mint_index } => {
+                let accepted = giveaway.accepted_mints.get(mint_index as usize).ok_or(GivefiError::MintNotAccepted)?;
+                let total_cost = math::mul(accepted.price, quantity)?;
+                let user_token_account = ctx.accounts.user_token_account.as_ref().ok_or(GivefiError::MintAccountsMissing)?;
+                let mint_vault = ctx.accounts.mint_vault.as_mut().ok_or(GivefiError::MintAccountsMissing)?;
+                let payment_mint = ctx.accounts.payment_mint.as_ref().ok_or(GivefiError::MintAccountsMissing)?;
+                let token_program = ctx.accounts.token_program.as_ref().ok_or(GivefiError::MintAccountsMissing)?;
+                require_keys_eq!(
+                    mint_vault.key(),
+                    mint_vault_address(giveaway.id, &accepted.mint, ctx.program_id),
+                    GivefiError::InvalidMintVault
+                );
+                require_keys_eq!(payment_mint.key(), accepted.mint, GivefiError::InvalidTokenMint);
+
+                let received = deposit_tokens(
+                    token_program,
+                    user_token_account,
+                    mint_vault,
+                    payment_mint,
+                    &ctx.accounts.user,
+                    total_cost,
+                )?;
 
-        token::transfer(cpi_ctx, giveaway.entry_cost_give.unwrap())?;
+                let accepted = &mut giveaway.accepted_mints[mint_index as usize];
+                accepted.entries = math::add(accepted.entries, quantity)?;
+                accepted.pool = math::add(accepted.pool, received)?;
+                accepted.ledger.collected = math::add(accepted.ledger.collected, received)?;
+                received
+            }
+        };
 
         let entry = &mut ctx.accounts.entry;
         entry.giveaway_id = giveaway.id;
         entry.user = ctx.accounts.user.key();
-        entry.entry_number = giveaway.current_entries;
+        entry.payer = ctx.accounts.user.key();
+        entry.first_entry_number = giveaway.current_entries;
+        entry.count = tickets;
+        entry.bonus_tickets = bonus_tickets;
+        entry.amount_paid = received;
         entry.timestamp = clock.unix_timestamp;
-        entry.payment_type = PaymentType::Give;
+        entry.payment_type = payment_type;
         entry.claimed = false;
         entry.bump = ctx.bumps.entry;
 
-        giveaway.current_entries += 1;
-        giveaway.give_entries += 1;
+        giveaway.current_entries = math::add(giveaway.current_entries, tickets)?;
 
+        emit_cpi!(EntryPurchased {
+            giveaway_id: giveaway.id,
+            user: entry.user,
+            first_entry_number: entry.first_entry_number,
+            tickets,
+            bonus_tickets,
+            payment_type: entry.payment_type.clone(),
+            amount_paid: received,
+            total_entries: giveaway.current_entries,
+        });
         Ok(())
     }
 
@@ -140,13 +855,16 @@ pub mod givefi {
         let giveaway = &mut ctx.accounts.giveaway;
         let clock = Clock::get()?;
 
-        require!(giveaway.is_active, GivefiError::GiveawayNotActive);
+        require!(giveaway.status == GiveawayStatus::Open, GivefiError::GiveawayNotActive);
         require!(giveaway.early_end_enabled, GivefiError::EarlyEndNotEnabled);
         require!(giveaway.current_entries >= giveaway.min_participants, GivefiError::MinParticipantsNotMet);
         require!(giveaway.authority == ctx.accounts.authority.key(), GivefiError::UnauthorizedEarlyEnd);
         require!(clock.unix_timestamp < giveaway.end_timestamp, GivefiError::GiveawayAlreadyEnded);
 
         giveaway.end_timestamp = clock.unix_timestamp;
+        let closed = giveaway.transition(GiveawayStatus::Closed)?;
+
+        emit_cpi!(closed);
         Ok(())
     }
 
@@ -154,41 +872,148 @@ pub mod givefi {
         let giveaway = &mut ctx.accounts.giveaway;
         let clock = Clock::get()?;
 
-        require!(giveaway.is_active, GivefiError::GiveawayNotActive);
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_DRAWS), GivefiError::ProgramPaused);
+        require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
         require!(clock.unix_timestamp >= giveaway.end_timestamp, GivefiError::GiveawayNotEnded);
-        require!(giveaway.winner.is_none(), GivefiError::WinnerAlreadyDrawn);
+        let closed = giveaway.close_if_ended(clock.unix_timestamp)?;
+        require!(
+            giveaway.status != GiveawayStatus::AwaitingRandomness,
+            GivefiError::RandomnessAlreadyRequested
+        );
+        require!(giveaway.status == GiveawayStatus::Closed, GivefiError::GiveawayNotActive);
         require!(giveaway.current_entries >= giveaway.min_participants, GivefiError::MinParticipantsNotMet);
+        // Without an oracle the request starts the authority's reveal, so only it may choose when
+        if ctx.accounts.program_state.oracle_program == Pubkey::default() {
+            require_keys_eq!(ctx.accounts.authority.key(), giveaway.authority, GivefiError::InvalidOwner);
+        }
+
+        // Pin a future slot; the authority's committed seed is mixed with its hash on reveal, and
+        // the fallback draws from it if nobody reveals
+        let (slot, slot_hash) = latest_slot_hash(&ctx.accounts.slot_hashes.to_account_info())?;
+        giveaway.recorded_slot = clock.slot.saturating_add(DRAW_SLOT_DELAY);
+        giveaway.recorded_slot_hash = [0u8; 32];
+        giveaway.oracle_requested = ctx.accounts.program_state.oracle_program != Pubkey::default();
+        let requested = giveaway.transition(GiveawayStatus::AwaitingRandomness)?;
+
+        // Snapshot the configured oracle so a later config change cannot redirect this request
+        let randomness_request = &mut ctx.accounts.randomness_request;
+        let id_bytes = giveaway.id.to_le_bytes();
+        randomness_request.giveaway_id = giveaway.id;
+        randomness_request.oracle_program = ctx.accounts.program_state.oracle_program;
+        randomness_request.seed = hashv(&[&id_bytes, &slot_hash]).to_bytes();
+        randomness_request.request_slot = slot;
+        randomness_request.fulfilled = false;
+        randomness_request.result = [0u8; 32];
+        randomness_request.payer = ctx.accounts.authority.key();
+        randomness_request.bump = ctx.bumps.randomness_request;
+
+        msg!("Randomness requested for giveaway {} at slot {}", giveaway.id, slot);
+        if let Some(event) = closed {
+            emit_cpi!(event);
+        }
+        emit_cpi!(requested);
+        emit_cpi!(RandomnessRequested {
+            giveaway_id: giveaway.id,
+            oracle_program: randomness_request.oracle_program,
+            seed: randomness_request.seed,
+            slot,
+            draw_slot: giveaway.recorded_slot,
+        });
+        Ok(())
+    }
+
+    pub fn fulfill_randomness(ctx: Context<FulfillRandomness>, result: [u8; 32]) -> Result<()> {
+        let giveaway = &mut ctx.accounts.giveaway;
+        let randomness_request = &mut ctx.accounts.randomness_request;
+
+        require!(randomness_request.oracle_program != Pubkey::default(), GivefiError::OracleNotConfigured);
+        let (oracle_authority, _) =
+            Pubkey::find_program_address(&[ORACLE_AUTHORITY_SEED], &randomness_request.oracle_program);
+        require_keys_eq!(ctx.accounts.oracle_authority.key(), oracle_authority, GivefiError::InvalidOracle);
+        require!(!randomness_request.fulfilled, GivefiError::RandomnessAlreadyFulfilled);
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_DRAWS), GivefiError::ProgramPaused);
+        require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
+        require!(giveaway.winners.is_empty(), GivefiError::WinnerAlreadyDrawn);
+        require!(giveaway.status == GiveawayStatus::AwaitingRandomness, GivefiError::RandomnessNotRequested);
+
+        let random_seed = hashv(&[&result, &randomness_request.seed]).to_bytes();
+        let winners = draw_winners(&random_seed, giveaway.current_entries, giveaway.total_winners());
+
+        randomness_request.fulfilled = true;
+        randomness_request.result = result;
+
+        msg!("Oracle winners selected: entries {:?}", winners);
+        giveaway.record_winners(winners, Clock::get()?.unix_timestamp);
+        let drawn = giveaway.transition(GiveawayStatus::Drawn)?;
+
+        emit_cpi!(WinnerDrawn {
+            giveaway_id: giveaway.id,
+            winners: giveaway.winners.clone(),
+            source: DrawSource::Oracle,
+            commitment_slashed: false,
+        });
+        emit_cpi!(drawn);
 
-        // For now, mark randomness as requested and use fallback method
-        // TODO: Implement proper Switchboard VRF integration when updated
-        giveaway.randomness_requested = true;
-        
-        msg!("Randomness requested for giveaway {}", giveaway.id);
         Ok(())
     }
 
-    pub fn settle_randomness_and_pick_winner(ctx: Context<SettleRandomness>) -> Result<()> {
+    pub fn settle_randomness_and_pick_winner(ctx: Context<SettleRandomness>, seed_preimage: [u8; 32]) -> Result<()> {
         let giveaway = &mut ctx.accounts.giveaway;
         let clock = Clock::get()?;
 
-        require!(giveaway.is_active, GivefiError::GiveawayNotActive);
-        require!(giveaway.randomness_requested, GivefiError::RandomnessNotRequested);
-        require!(giveaway.winner.is_none(), GivefiError::WinnerAlreadyDrawn);
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_DRAWS), GivefiError::ProgramPaused);
+        require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
+        require!(giveaway.winners.is_empty(), GivefiError::WinnerAlreadyDrawn);
+        require!(giveaway.status == GiveawayStatus::AwaitingRandomness, GivefiError::RandomnessNotRequested);
+        require!(
+            ctx.accounts.randomness_request.oracle_program == Pubkey::default(),
+            GivefiError::OracleRandomnessPending
+        );
+        require!(
+            clock.unix_timestamp < giveaway.end_timestamp.saturating_add(REVEAL_WINDOW_SECS),
+            GivefiError::RevealWindowClosed
+        );
+        require!(
+            hash(&seed_preimage).to_bytes() == giveaway.randomness_commitment,
+            GivefiError::InvalidReveal
+        );
+        giveaway
+            .pinned_slot_hash(&ctx.accounts.slot_hashes, clock.slot)?
+            .ok_or(GivefiError::SlotHashUnavailable)?;
+
+        let random_seed = hashv(&[&seed_preimage, &giveaway.recorded_slot_hash]).to_bytes();
+        let winners = draw_winners(&random_seed, giveaway.current_entries, giveaway.total_winners());
+
+        msg!("Winners selected: entries {:?}", winners);
+        giveaway.record_winners(winners, clock.unix_timestamp);
+        let drawn = giveaway.transition(GiveawayStatus::Drawn)?;
+
+        emit_cpi!(WinnerDrawn {
+            giveaway_id: giveaway.id,
+            winners: giveaway.winners.clone(),
+            source: DrawSource::Reveal,
+            commitment_slashed: false,
+        });
+        emit_cpi!(drawn);
 
-        // Use clock-based randomness as temporary solution
-        // TODO: Implement proper VRF settlement when Switchboard is updated
-        let slot = clock.slot;
-        let timestamp = clock.unix_timestamp;
-        
-        // Combine slot and timestamp for pseudo-randomness
-        let random_seed = ((slot as u64) ^ (timestamp as u64)).wrapping_mul(16777619);
-        let winning_entry = random_seed % giveaway.current_entries;
-        
-        giveaway.winner = Some(winning_entry);
-        giveaway.is_successful = true;
-        giveaway.is_active = false;
+        Ok(())
+    }
+
+    // Stores the pinned slot's hash on the giveaway before it ages out of SlotHashes (about 512
+    // slots), so the reveal stays possible for the whole window and the fallback draws from the
+    // slot pinned by request_randomness. Anyone may call it. It isn't pausable, since it only
+    // keeps a public value the draw is already bound to.
+    pub fn record_draw_slot(ctx: Context<RecordDrawSlot>) -> Result<()> {
+        let giveaway = &mut ctx.accounts.giveaway;
 
-        msg!("Winner selected: entry #{}", winning_entry);
+        require!(giveaway.status.is_live() && giveaway.recorded_slot != 0, GivefiError::RandomnessNotRequested);
+        require!(giveaway.recorded_slot_hash == [0u8; 32], GivefiError::DrawSlotAlreadyRecorded);
+        let slot_hash = giveaway
+            .pinned_slot_hash(&ctx.accounts.slot_hashes, Clock::get()?.slot)?
+            .ok_or(GivefiError::SlotHashUnavailable)?;
+
+        msg!("Giveaway {} draw slot {} recorded", giveaway.id, giveaway.recorded_slot);
+        emit_cpi!(DrawSlotRecorded { giveaway_id: giveaway.id, slot: giveaway.recorded_slot, slot_hash });
         Ok(())
     }
 
@@ -196,33 +1021,66 @@ pub mod givefi {
         let giveaway = &mut ctx.accounts.giveaway;
         let clock = Clock::get()?;
 
-        require!(giveaway.is_active, GivefiError::GiveawayNotActive);
-        require!(clock.unix_timestamp >= giveaway.end_timestamp + 3600, GivefiError::FallbackTooEarly); // 1 hour after end
-        require!(giveaway.winner.is_none(), GivefiError::WinnerAlreadyDrawn);
+        require!(giveaway.status.is_live(), GivefiError::GiveawayNotActive);
+        require!(
+            clock.unix_timestamp >= giveaway.end_timestamp.saturating_add(REVEAL_WINDOW_SECS),
+            GivefiError::FallbackTooEarly
+        );
+        if let Some(event) = giveaway.close_if_ended(clock.unix_timestamp)? {
+            emit_cpi!(event);
+        }
 
         if giveaway.current_entries >= giveaway.min_participants {
-            giveaway.is_successful = true;
-            
-            // Use clock-based pseudo-randomness as fallback
-            let slot = clock.slot;
-            let timestamp = clock.unix_timestamp;
-            let epoch = clock.epoch;
-            
-            // Combine multiple clock values for better pseudo-randomness
-            let random_seed = ((slot as u64) ^ (timestamp as u64) ^ (epoch as u64))
-                .wrapping_mul(16777619)
-                .wrapping_add(1013904223);
-            
-            let winning_entry = random_seed % giveaway.current_entries;
-            giveaway.winner = Some(winning_entry);
-            
-            msg!("Fallback winner selected: entry #{}", winning_entry);
+            // Only drawing is pausable; marking a giveaway failed keeps refunds reachable
+            require!(!ctx.accounts.program_state.is_paused(PAUSE_DRAWS), GivefiError::ProgramPaused);
+            require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
+
+            // Nobody settled in time: draw deterministically from the hash of the slot pinned by
+            // request_randomness. With no pin yet this call only pins a future slot and a later
+            // call draws. If the authority owed a reveal, its jackpot share is forfeited to the
+            // treasury.
+            let slot_hash = match giveaway.recorded_slot {
+                0 => None,
+                _ => giveaway.pinned_slot_hash(&ctx.accounts.slot_hashes, clock.slot)?,
+            };
+            let Some(slot_hash) = slot_hash else {
+                // A pin whose hash aged out unrecorded is replaced only by the platform. Anyone
+                // else could have seen that hash, disliked the draw it gave and waited it out.
+                if giveaway.recorded_slot != 0 {
+                    require_keys_eq!(
+                        ctx.accounts.authority.key(),
+                        ctx.accounts.program_state.authority,
+                        GivefiError::SlotHashUnavailable
+                    );
+                }
+                giveaway.recorded_slot = clock.slot.saturating_add(DRAW_SLOT_DELAY);
+                msg!("Fallback draw pinned to slot {}", giveaway.recorded_slot);
+                emit_cpi!(DrawSlotPinned { giveaway_id: giveaway.id, slot: giveaway.recorded_slot });
+                return Ok(());
+            };
+            giveaway.commitment_slashed = !giveaway.oracle_requested;
+
+            let id_bytes = giveaway.id.to_le_bytes();
+            let random_seed = hashv(&[b"fallback", &id_bytes, &slot_hash]).to_bytes();
+            let winners = draw_winners(&random_seed, giveaway.current_entries, giveaway.total_winners());
+
+            msg!("Fallback winners selected: entries {:?}", winners);
+            giveaway.record_winners(winners, clock.unix_timestamp);
+            let drawn = giveaway.transition(GiveawayStatus::Drawn)?;
+
+            emit_cpi!(WinnerDrawn {
+                giveaway_id: giveaway.id,
+                winners: giveaway.winners.clone(),
+                source: DrawSource::Fallback,
+                commitment_slashed: giveaway.commitment_slashed,
+            });
+            emit_cpi!(drawn);
         } else {
-            giveaway.is_successful = false;
             msg!("Giveaway failed: insufficient participants");
+            let failed = giveaway.transition(GiveawayStatus::Failed)?;
+            emit_cpi!(failed);
         }
 
-        giveaway.is_active = false;
         Ok(())
     }
 
@@ -230,42 +1088,105 @@ pub mod givefi {
         let giveaway = &mut ctx.accounts.giveaway;
         let entry = &mut ctx.accounts.entry;
 
-        require!(!giveaway.is_active, GivefiError::GiveawayStillActive);
-        require!(giveaway.is_successful, GivefiError::GiveawayNotSuccessful);
-        require!(giveaway.winner.is_some(), GivefiError::NoWinnerDrawn);
-        require!(!giveaway.prize_claimed && !giveaway.jackpot_claimed, GivefiError::PrizeAlreadyClaimed);
-        require!(entry.entry_number == giveaway.winner.unwrap(), GivefiError::NotWinner);
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_CLAIMS), GivefiError::ProgramPaused);
+        require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
+        require!(giveaway.status.is_claimable(), GivefiError::GiveawayNotSuccessful);
+        // A queued redraw picks by rank among the unretired tickets, so nothing may retire more
+        // until it has run
+        require!(giveaway.pending_redraws.is_empty(), GivefiError::RedrawInProgress);
+        let positions = giveaway.winning_positions(entry.first_entry_number, entry.count)?;
+        require!(!positions.is_empty(), GivefiError::NotWinner);
+        require!(
+            !giveaway.claim_window_closed(&positions, Clock::get()?.unix_timestamp),
+            GivefiError::ClaimWindowClosed
+        );
+        let tiers = giveaway.tiers_won(entry.first_entry_number, entry.count)?;
         require!(entry.user == ctx.accounts.user.key(), GivefiError::UnauthorizedClaim);
         require!(!entry.claimed, GivefiError::PrizeAlreadyClaimed);
 
-        giveaway.prize_claimed = true;
+        let mut prize_share = 0;
+        if let Some(prize_mint) = giveaway.prize_mint {
+            let prize_vault = ctx.accounts.prize_vault.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+            let user_prize_account = ctx.accounts.user_prize_account.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+            let prize_mint_account = ctx.accounts.prize_mint.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+            require_keys_eq!(prize_mint_account.key(), prize_mint, GivefiError::InvalidPrizeMint);
+            require_keys_eq!(user_prize_account.mint, prize_mint, GivefiError::InvalidPrizeMint);
+
+            prize_share = tiers_slice(giveaway.prize_amount, &giveaway.prize_tiers, &tiers)?;
+            let id_bytes = giveaway.id.to_le_bytes();
+            let seeds = &[b"giveaway", &id_bytes[..], &[giveaway.bump]];
+            let signer = &[&seeds[..]];
+
+            let transfer_instruction = TransferChecked {
+                from: prize_vault.to_account_info(),
+                mint: prize_mint_account.to_account_info(),
+                to: user_prize_account.to_account_info(),
+                authority: giveaway.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                transfer_instruction,
+                signer,
+            );
+            token_interface::transfer_checked(cpi_ctx, prize_share, prize_mint_account.decimals)?;
+        }
+
+        giveaway.prizes_claimed = giveaway.prizes_claimed.checked_add(tiers.len() as u8).ok_or(GivefiError::MathOverflow)?;
+        let settled = giveaway.settle_if_done()?;
+        giveaway.retire_tickets(entry.first_entry_number, entry.count);
         entry.claimed = true;
 
-        ctx.accounts.entry.close(ctx.accounts.treasury_wallet.to_account_info())?;
+        msg!("Tiers {:?} claimed by entry #{}", tiers, entry.first_entry_number);
+        emit_cpi!(PrizeClaimed {
+            giveaway_id: giveaway.id,
+            user: entry.user,
+            first_entry_number: entry.first_entry_number,
+            tiers: tiers.iter().map(|&tier| tier as u8).collect(),
+            prize_mint: giveaway.prize_mint,
+            amount: prize_share,
+        });
+        if let Some(event) = settled {
+            emit_cpi!(event);
+        }
+
+        ctx.accounts.entry.close(ctx.accounts.payer.to_account_info())?;
+        release_participant(&mut ctx.accounts.participant, &ctx.accounts.payer)?;
 
         Ok(())
     }
 
-    pub fn claim_jackpot(ctx: Context<ClaimJackpot>) -> Result<()> {
+    // remaining_accounts holds [mint, vault, winner account, creator account, treasury account,
+    // token program] for every accepted mint that took entries, in `accepted_mints` order
+    pub fn claim_jackpot<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimJackpot<'info>>) -> Result<()> {
     // Only borrow as immutable for CPI and calculations
     let entry = &mut ctx.accounts.entry;
-    let _program_state = &ctx.accounts.program_state;
 
-    require!(!ctx.accounts.giveaway.is_active, GivefiError::GiveawayStillActive);
-    require!(ctx.accounts.giveaway.is_successful, GivefiError::GiveawayNotSuccessful);
+    require!(!ctx.accounts.program_state.is_paused(PAUSE_CLAIMS), GivefiError::ProgramPaused);
+    require!(!ctx.accounts.giveaway.frozen, GivefiError::GiveawayFrozen);
+    require!(ctx.accounts.giveaway.status.is_claimable(), GivefiError::GiveawayNotSuccessful);
     require!(ctx.accounts.giveaway.jackpot_option_enabled, GivefiError::JackpotNotEnabled);
-    require!(ctx.accounts.giveaway.winner.is_some(), GivefiError::NoWinnerDrawn);
-    require!(!ctx.accounts.giveaway.prize_claimed && !ctx.accounts.giveaway.jackpot_claimed, GivefiError::PrizeAlreadyClaimed);
-    require!(entry.entry_number == ctx.accounts.giveaway.winner.unwrap(), GivefiError::NotWinner);
+    require!(ctx.accounts.giveaway.pending_redraws.is_empty(), GivefiError::RedrawInProgress);
+    let positions = ctx.accounts.giveaway.winning_positions(entry.first_entry_number, entry.count)?;
+    require!(!positions.is_empty(), GivefiError::NotWinner);
+    require!(
+        !ctx.accounts.giveaway.claim_window_closed(&positions, Clock::get()?.unix_timestamp),
+        GivefiError::ClaimWindowClosed
+    );
+    let tiers = ctx.accounts.giveaway.tiers_won(entry.first_entry_number, entry.count)?;
     require!(entry.user == ctx.accounts.user.key(), GivefiError::UnauthorizedClaim);
     require!(!entry.claimed, GivefiError::PrizeAlreadyClaimed);
 
     let giveaway = &ctx.accounts.giveaway; // immutable borrow for calculations
+    let shares = |pool: u64| giveaway.claim_shares(pool, &tiers);
 
-    let total_sol_collected = giveaway.sol_entries * giveaway.entry_cost_sol;
-    let winner_sol_amount = (total_sol_collected * 33) / 100;
-    let owner_sol_amount = (total_sol_collected * 60) / 100;
-    let treasury_sol_amount = (total_sol_collected * 7) / 100;
+    let sol_shares = shares(giveaway.sol_pool()?)?;
+    let JackpotShares {
+        slice: total_sol_collected,
+        winner: winner_sol_amount,
+        creator: owner_sol_amount,
+        treasury: treasury_sol_amount,
+    } = sol_shares;
 
     let giveaway_id_bytes = giveaway.id.to_le_bytes();
     let vault_seeds = &[b"giveaway_vault", &giveaway_id_bytes[..], &[ctx.bumps.giveaway_vault]];
@@ -307,74 +1228,461 @@ pub mod givefi {
         );
         anchor_lang::system_program::transfer(cpi_ctx, treasury_sol_amount)?;
     }
+    if total_sol_collected > 0 {
+        emit_cpi!(JackpotPaid {
+            giveaway_id: giveaway.id,
+            user: entry.user,
+            first_entry_number: entry.first_entry_number,
+            mint: None,
+            winner_amount: winner_sol_amount,
+            creator_amount: owner_sol_amount,
+            treasury_amount: treasury_sol_amount,
+        });
+    }
 
-    if giveaway.give_entries > 0 && giveaway.entry_cost_give.is_some() {
-        let total_give_collected = giveaway.give_entries * giveaway.entry_cost_give.unwrap();
-        let winner_give_amount = (total_give_collected * 33) / 100;
-        let owner_give_amount = (total_give_collected * 60) / 100;
-        let treasury_give_amount = (total_give_collected * 7) / 100;
+    let id_bytes = giveaway.id.to_le_bytes();
+    let seeds = &[b"giveaway", &id_bytes[..], &[giveaway.bump]];
+    let signer = &[&seeds[..]];
+    // Booked on each token vault's ledger once the giveaway can be borrowed mutably
+    let mut token_shares = Vec::new();
 
-        let id_bytes = giveaway.id.to_le_bytes();
-        let seeds = &[b"giveaway", &id_bytes[..], &[giveaway.bump]];
-        let signer = &[&seeds[..]];
+    if giveaway.give_pool > 0 {
+        let user_token_account = ctx.accounts.user_token_account.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+        let owner_token_account = ctx.accounts.owner_token_account.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+        let treasury_token_account =
+            ctx.accounts.treasury_token_account.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+        let giveaway_token_vault = ctx.accounts.giveaway_token_vault.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+        let give_mint = ctx.accounts.give_mint.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+        let token_program = ctx.accounts.token_program.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+        require_keys_eq!(give_mint.key(), giveaway_token_vault.mint, GivefiError::InvalidTokenMint);
+        require_keys_eq!(user_token_account.owner, ctx.accounts.user.key(), GivefiError::InvalidTokenOwner);
+        require_keys_eq!(owner_token_account.owner, giveaway.authority, GivefiError::InvalidTokenOwner);
+        require_keys_eq!(
+            treasury_token_account.owner,
+            ctx.accounts.treasury_wallet.key(),
+            GivefiError::InvalidTokenOwner
+        );
 
-        if winner_give_amount > 0 {
-            let transfer_instruction = Transfer {
-                from: ctx.accounts.giveaway_token_vault.to_account_info(),
-                to: ctx.accounts.user_token_account.to_account_info(),
-                authority: ctx.accounts.giveaway.to_account_info(),
-            };
-            let cpi_ctx = CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                transfer_instruction,
-                signer,
-            );
-            token::transfer(cpi_ctx, winner_give_amount)?;
-        }
-        if owner_give_amount > 0 {
-            let transfer_instruction = Transfer {
-                from: ctx.accounts.giveaway_token_vault.to_account_info(),
-                to: ctx.accounts.owner_token_account.to_account_info(),
-                authority: ctx.accounts.giveaway.to_account_info(),
-            };
-            let cpi_ctx = CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                transfer_instruction,
-                signer,
-            );
-            token::transfer(cpi_ctx, owner_give_amount)?;
-        }
-        if treasury_give_amount > 0 {
-            let transfer_instruction = Transfer {
-                from: ctx.accounts.giveaway_token_vault.to_account_info(),
-                to: ctx.accounts.treasury_token_account.to_account_info(),
-                authority: ctx.accounts.giveaway.to_account_info(),
-            };
-            let cpi_ctx = CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                transfer_instruction,
+        let give_shares = shares(giveaway.give_pool)?;
+        let JackpotShares {
+            winner: winner_give_amount,
+            creator: owner_give_amount,
+            treasury: treasury_give_amount,
+            ..
+        } = give_shares;
+
+        for (destination, amount) in [
+            (user_token_account, winner_give_amount),
+            (owner_token_account, owner_give_amount),
+            (treasury_token_account, treasury_give_amount),
+        ] {
+            transfer_from_vault(
+                token_program,
+                &giveaway_token_vault.to_account_info(),
+                give_mint,
+                &destination.to_account_info(),
+                &ctx.accounts.giveaway,
                 signer,
-            );
-            token::transfer(cpi_ctx, treasury_give_amount)?;
+                amount,
+            )?;
         }
+        emit_cpi!(JackpotPaid {
+            giveaway_id: giveaway.id,
+            user: entry.user,
+            first_entry_number: entry.first_entry_number,
+            mint: Some(give_mint.key()),
+            winner_amount: winner_give_amount,
+            creator_amount: owner_give_amount,
+            treasury_amount: treasury_give_amount,
+        });
+        token_shares.push((PaymentType::Give, give_shares));
     }
 
-    // Now, after all immutable borrows, borrow as mutable to update fields
+    // Each accepted mint's pool is split on its own, out of its own vault and through its own
+    // token program, since GIVE and the accepted mints may mix classic SPL and Token-2022
+    let mut mint_accounts = ctx.remaining_accounts.chunks(6);
+    for (mint_index, accepted) in giveaway.accepted_mints.iter().enumerate().filter(|(_, accepted)| accepted.pool > 0) {
+        let Some([mint_info, vault_info, user_info, owner_info, treasury_info, program_info]) = mint_accounts.next()
+        else {
+            return err!(GivefiError::MintAccountsMissing);
+        };
+        let mint = load_mint(mint_info, &accepted.mint)?;
+        let token_program = load_token_program(program_info, mint_info)?;
+        require_keys_eq!(
+            vault_info.key(),
+            mint_vault_address(giveaway.id, &accepted.mint, ctx.program_id),
+            GivefiError::InvalidMintVault
+        );
+        check_token_account(user_info, &accepted.mint, &ctx.accounts.user.key())?;
+        check_token_account(owner_info, &accepted.mint, &giveaway.authority)?;
+        check_token_account(treasury_info, &accepted.mint, &ctx.accounts.program_state.treasury_wallet)?;
+
+        let mint_shares = shares(accepted.pool)?;
+        let JackpotShares { winner: winner_amount, creator: owner_amount, treasury: treasury_amount, .. } = mint_shares;
+
+        for (destination, amount) in [(user_info, winner_amount), (owner_info, owner_amount), (treasury_info, treasury_amount)] {
+            transfer_from_vault(
+                &token_program,
+                vault_info,
+                &mint,
+                destination,
+                &ctx.accounts.giveaway,
+                signer,
+                amount,
+            )?;
+        }
+        emit_cpi!(JackpotPaid {
+            giveaway_id: giveaway.id,
+            user: entry.user,
+            first_entry_number: entry.first_entry_number,
+            mint: Some(accepted.mint),
+            winner_amount,
+            creator_amount: owner_amount,
+            treasury_amount,
+        });
+        token_shares.push((PaymentType::Spl { mint_index: mint_index as u8 }, mint_shares));
+    }
+
+    // Now, after all immutable borrows, borrow as mutable to update fields
     let giveaway = &mut ctx.accounts.giveaway;
-    giveaway.jackpot_claimed = true;
+    giveaway.sol_ledger.book_jackpot(&sol_shares)?;
+    for (payment_type, shares) in &token_shares {
+        giveaway.ledger_mut(payment_type)?.book_jackpot(shares)?;
+    }
+    giveaway.sol_ledger.check(&ctx.accounts.giveaway_vault)?;
+    giveaway.jackpots_claimed = giveaway.jackpots_claimed.checked_add(tiers.len() as u8).ok_or(GivefiError::MathOverflow)?;
+    let settled = giveaway.settle_if_done()?;
+    giveaway.retire_tickets(entry.first_entry_number, entry.count);
     entry.claimed = true;
 
-    ctx.accounts.entry.close(ctx.accounts.treasury_wallet.to_account_info())?;
+    ctx.accounts.entry.close(ctx.accounts.payer.to_account_info())?;
+    release_participant(&mut ctx.accounts.participant, &ctx.accounts.payer)?;
+    if let Some(event) = settled {
+        emit_cpi!(event);
+    }
 
     Ok(())
     }
 
-    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+    // Returns the escrowed prize to the creator once the giveaway failed or the DAO cancelled it,
+    // or once every winner has claimed (winners who took the jackpot leave their prize slice behind)
+    pub fn reclaim_prize(ctx: Context<ReclaimPrize>) -> Result<()> {
+        let giveaway = &ctx.accounts.giveaway;
+
+        require!(!giveaway.status.is_live(), GivefiError::GiveawayStillActive);
+        require!(
+            matches!(giveaway.status, GiveawayStatus::Failed | GiveawayStatus::Settled | GiveawayStatus::Cancelled),
+            GivefiError::GiveawayWasSuccessful
+        );
+
+        let remaining = release_prize_escrow(
+            giveaway,
+            &ctx.accounts.prize_vault,
+            &ctx.accounts.prize_mint,
+            ctx.accounts.authority_prize_account.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        msg!("Returned {} escrowed prize tokens to creator", remaining);
+        emit_cpi!(PrizeReclaimed {
+            giveaway_id: giveaway.id,
+            authority: ctx.accounts.authority.key(),
+            mint: ctx.accounts.prize_mint.key(),
+            amount: remaining,
+        });
+        Ok(())
+    }
+
+    // Once the claim window has passed, anyone can deal with an unclaimed winning entry: under
+    // the Redraw policy its winning tickets are redrawn from tickets that haven't won, otherwise
+    // its share of the escrowed prize and of every jackpot pool goes to the policy's recipient.
+    // remaining_accounts holds [mint, vault, recipient token account, token program] for GIVE (when
+    // collected) and then for every accepted mint that took entries.
+    pub fn reclaim_unclaimed<'info>(ctx: Context<'_, '_, 'info, 'info, ReclaimUnclaimed<'info>>) -> Result<()> {
+        let giveaway = &ctx.accounts.giveaway;
+        let entry = &ctx.accounts.entry;
+        let clock = Clock::get()?;
+
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_CLAIMS), GivefiError::ProgramPaused);
+        require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
+        require!(giveaway.status.is_claimable(), GivefiError::GiveawayNotSuccessful);
+        let deadline = giveaway.claim_deadline.ok_or(GivefiError::NoClaimDeadline)?;
+        require!(giveaway.pending_redraws.is_empty(), GivefiError::RedrawInProgress);
+        let positions = giveaway.winning_positions(entry.first_entry_number, entry.count)?;
+        require!(!positions.is_empty(), GivefiError::NotWinner);
+        require!(giveaway.claim_window_closed(&positions, clock.unix_timestamp), GivefiError::ClaimWindowOpen);
+        require!(!entry.claimed, GivefiError::PrizeAlreadyClaimed);
+
+        // The entry is closed below either way, so none of its tickets may be redrawn later
+        let retired = ctx.accounts.giveaway.retire_tickets(entry.first_entry_number, entry.count);
+        let giveaway = &ctx.accounts.giveaway;
+
+        if retired && giveaway.redraw_pool() >= positions.len() as u64 {
+            // Replacements come from the hash of a slot nobody has seen yet, drawn by redraw_winners
+            let giveaway = &mut ctx.accounts.giveaway;
+            giveaway.pending_redraws = positions.iter().map(|&position| position as u8).collect();
+            giveaway.redraw_slot = clock.slot.saturating_add(DRAW_SLOT_DELAY);
+
+            msg!("Redraw for unclaimed entry #{} pinned to slot {}", entry.first_entry_number, giveaway.redraw_slot);
+            emit_cpi!(RedrawQueued {
+                giveaway_id: giveaway.id,
+                forfeited_entry_number: Some(entry.first_entry_number),
+                positions: giveaway.pending_redraws.clone(),
+                slot: giveaway.redraw_slot,
+            });
+        } else {
+            // Redraw falls back to the creator once no tickets are left to draw from, or once it
+            // has retired MAX_RETIRED_ENTRIES entries
+            let recipient_key = match deadline.policy {
+                UnclaimedPolicy::Redraw | UnclaimedPolicy::Creator => giveaway.authority,
+                UnclaimedPolicy::Treasury => ctx.accounts.program_state.treasury_wallet,
+                UnclaimedPolicy::Charity { wallet } => wallet,
+            };
+            let recipient = ctx.accounts.recipient.as_ref().ok_or(GivefiError::InvalidUnclaimedRecipient)?;
+            require_keys_eq!(recipient.key(), recipient_key, GivefiError::InvalidUnclaimedRecipient);
+
+            let tiers: Vec<usize> = positions.iter().filter_map(|&position| giveaway.tier_at(position)).collect();
+            let pool_slice = |pool: u64| tiers_slice(pool, &giveaway.prize_tiers, &tiers);
+            let id_bytes = giveaway.id.to_le_bytes();
+            let seeds = &[b"giveaway", &id_bytes[..], &[giveaway.bump]];
+            let signer = &[&seeds[..]];
+
+            if let Some(prize_mint) = giveaway.prize_mint {
+                let prize_vault = ctx.accounts.prize_vault.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+                let recipient_prize_account =
+                    ctx.accounts.recipient_prize_account.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+                let prize_mint_account = ctx.accounts.prize_mint.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+                let token_program = ctx.accounts.token_program.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+                require_keys_eq!(prize_mint_account.key(), prize_mint, GivefiError::InvalidPrizeMint);
+                require_keys_eq!(recipient_prize_account.mint, prize_mint, GivefiError::InvalidPrizeMint);
+                require_keys_eq!(recipient_prize_account.owner, recipient_key, GivefiError::InvalidTokenOwner);
+
+                let prize_share = pool_slice(giveaway.prize_amount)?;
+                transfer_from_vault(
+                    token_program,
+                    &prize_vault.to_account_info(),
+                    prize_mint_account,
+                    &recipient_prize_account.to_account_info(),
+                    giveaway,
+                    signer,
+                    prize_share,
+                )?;
+                emit_cpi!(UnclaimedRouted {
+                    giveaway_id: giveaway.id,
+                    first_entry_number: entry.first_entry_number,
+                    recipient: recipient_key,
+                    mint: Some(prize_mint),
+                    prize: true,
+                    amount: prize_share,
+                });
+            }
+
+            let mut sol_share = 0;
+            let mut token_routed = Vec::new();
+            if giveaway.jackpot_option_enabled {
+                sol_share = pool_slice(giveaway.sol_pool()?)?;
+                if sol_share > 0 {
+                    let vault_seeds = &[b"giveaway_vault", &id_bytes[..], &[ctx.bumps.giveaway_vault]];
+                    let vault_signer = &[&vault_seeds[..]];
+                    let cpi_ctx = CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.giveaway_vault.to_account_info(),
+                            to: recipient.to_account_info(),
+                        },
+                        vault_signer,
+                    );
+                    anchor_lang::system_program::transfer(cpi_ctx, sol_share)?;
+                    emit_cpi!(UnclaimedRouted {
+                        giveaway_id: giveaway.id,
+                        first_entry_number: entry.first_entry_number,
+                        recipient: recipient_key,
+                        mint: None,
+                        prize: false,
+                        amount: sol_share,
+                    });
+                }
+
+                let mut mint_accounts = ctx.remaining_accounts.chunks(4);
+                if giveaway.give_pool > 0 {
+                    let Some([mint_info, vault_info, recipient_info, program_info]) = mint_accounts.next() else {
+                        return err!(GivefiError::GiveAccountsMissing);
+                    };
+                    let (give_vault, _) = Pubkey::find_program_address(&[b"token_vault", &id_bytes], ctx.program_id);
+                    require_keys_eq!(vault_info.key(), give_vault, GivefiError::InvalidMintVault);
+                    let vault: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(vault_info)?;
+                    let mint = load_mint(mint_info, &vault.mint)?;
+                    let token_program = load_token_program(program_info, mint_info)?;
+                    check_token_account(recipient_info, &vault.mint, &recipient_key)?;
+
+                    let amount = pool_slice(giveaway.give_pool)?;
+                    transfer_from_vault(&token_program, vault_info, &mint, recipient_info, giveaway, signer, amount)?;
+                    token_routed.push((PaymentType::Give, amount));
+                    emit_cpi!(UnclaimedRouted {
+                        giveaway_id: giveaway.id,
+                        first_entry_number: entry.first_entry_number,
+                        recipient: recipient_key,
+                        mint: Some(vault.mint),
+                        prize: false,
+                        amount,
+                    });
+                }
+                for (mint_index, accepted) in
+                    giveaway.accepted_mints.iter().enumerate().filter(|(_, accepted)| accepted.pool > 0)
+                {
+                    let Some([mint_info, vault_info, recipient_info, program_info]) = mint_accounts.next() else {
+                        return err!(GivefiError::MintAccountsMissing);
+                    };
+                    let mint = load_mint(mint_info, &accepted.mint)?;
+                    let token_program = load_token_program(program_info, mint_info)?;
+                    require_keys_eq!(
+                        vault_info.key(),
+                        mint_vault_address(giveaway.id, &accepted.mint, ctx.program_id),
+                        GivefiError::InvalidMintVault
+                    );
+                    check_token_account(recipient_info, &accepted.mint, &recipient_key)?;
+
+                    let amount = pool_slice(accepted.pool)?;
+                    transfer_from_vault(&token_program, vault_info, &mint, recipient_info, giveaway, signer, amount)?;
+                    token_routed.push((PaymentType::Spl { mint_index: mint_index as u8 }, amount));
+                    emit_cpi!(UnclaimedRouted {
+                        giveaway_id: giveaway.id,
+                        first_entry_number: entry.first_entry_number,
+                        recipient: recipient_key,
+                        mint: Some(accepted.mint),
+                        prize: false,
+                        amount,
+                    });
+                }
+            }
+
+            let giveaway = &mut ctx.accounts.giveaway;
+            for (payment_type, amount) in std::iter::once((PaymentType::Sol, sol_share)).chain(token_routed) {
+                let ledger = giveaway.ledger_mut(&payment_type)?;
+                if deadline.policy == UnclaimedPolicy::Treasury {
+                    ledger.fees = math::add(ledger.fees, amount)?;
+                } else {
+                    ledger.paid_out = math::add(ledger.paid_out, amount)?;
+                }
+            }
+            giveaway.sol_ledger.check(&ctx.accounts.giveaway_vault)?;
+            giveaway.forfeited = giveaway.forfeited.checked_add(positions.len() as u8).ok_or(GivefiError::MathOverflow)?;
+            if let Some(event) = giveaway.settle_if_done()? {
+                emit_cpi!(event);
+            }
+        }
+
+        ctx.accounts.entry.close(ctx.accounts.payer.to_account_info())?;
+        release_participant(&mut ctx.accounts.participant, &ctx.accounts.payer)?;
+        Ok(())
+    }
+
+    // Replaces forfeited winners once the slot pinned by reclaim_unclaimed has passed. Anyone may
+    // call it. A pin whose hash aged out first is replaced only by the platform, for the same
+    // reason as in draw_winner_fallback.
+    pub fn redraw_winners(ctx: Context<RedrawWinners>) -> Result<()> {
+        let giveaway = &mut ctx.accounts.giveaway;
+        let clock = Clock::get()?;
+
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_DRAWS), GivefiError::ProgramPaused);
+        require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
+        require!(!giveaway.pending_redraws.is_empty(), GivefiError::NoRedrawPending);
+        require!(clock.slot > giveaway.redraw_slot, GivefiError::SlotHashNotReady);
+
+        let Some(slot_hash) = draw_slot_hash(&ctx.accounts.slot_hashes, giveaway.redraw_slot)? else {
+            require_keys_eq!(
+                ctx.accounts.authority.key(),
+                ctx.accounts.program_state.authority,
+                GivefiError::SlotHashUnavailable
+            );
+            giveaway.redraw_slot = clock.slot.saturating_add(DRAW_SLOT_DELAY);
+            msg!("Redraw pinned to slot {}", giveaway.redraw_slot);
+            emit_cpi!(RedrawQueued {
+                giveaway_id: giveaway.id,
+                forfeited_entry_number: None,
+                positions: giveaway.pending_redraws.clone(),
+                slot: giveaway.redraw_slot,
+            });
+            return Ok(());
+        };
+
+        let id_bytes = giveaway.id.to_le_bytes();
+        let positions = std::mem::take(&mut giveaway.pending_redraws);
+        // Replacement winners get a full window of their own
+        let claim_deadline = giveaway.claim_deadline_from(clock.unix_timestamp);
+        for &position in &positions {
+            let seed = hashv(&[b"redraw", &id_bytes, &slot_hash, &[position]]).to_bytes();
+            // Pick straight among the eligible tickets, so a nearly exhausted pool costs no retries.
+            // reclaim_unclaimed checked there are enough, and no tickets are retired meanwhile.
+            let pick = entry_from_seed(&seed, giveaway.redraw_pool());
+            giveaway.winners[position as usize] = giveaway.nth_redraw_ticket(pick);
+            giveaway.winner_deadlines[position as usize] = claim_deadline;
+        }
+
+        msg!("Redrew winning positions {:?}", positions);
+        emit_cpi!(WinnersRedrawn {
+            giveaway_id: giveaway.id,
+            replacements: positions.iter().map(|&position| giveaway.winners[position as usize]).collect(),
+            positions,
+            claim_deadline,
+        });
+        Ok(())
+    }
+
+    // Creator or platform authority can call off a giveaway before it is drawn. A single DAO member
+    // can't: that would bypass the quorum, so the DAO goes through propose_cancellation instead.
+    // Entries then become refundable through claim_refund.
+    pub fn cancel_giveaway(ctx: Context<CancelGiveaway>) -> Result<()> {
+        let giveaway = &ctx.accounts.giveaway;
+        let canceller = ctx.accounts.canceller.key();
+
+        let is_creator = canceller == giveaway.authority;
+        let is_platform = canceller == ctx.accounts.program_state.authority;
+        require!(is_creator || is_platform, GivefiError::UnauthorizedCancel);
+
+        require!(giveaway.status.is_live(), GivefiError::GiveawayNotActive);
+        // Once the slot hash is pinned the creator can predict the draw, so it may no longer bail out
+        require!(
+            !(is_creator && giveaway.status == GiveawayStatus::AwaitingRandomness),
+            GivefiError::RandomnessAlreadyRequested
+        );
+
+        let mut prize_returned = 0;
+        if giveaway.prize_mint.is_some() && giveaway.prize_funded {
+            let prize_vault = ctx.accounts.prize_vault.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+            let creator_prize_account =
+                ctx.accounts.creator_prize_account.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+            let prize_mint = ctx.accounts.prize_mint.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(GivefiError::PrizeAccountsMissing)?;
+            require_keys_eq!(creator_prize_account.owner, giveaway.authority, GivefiError::InvalidOwner);
+
+            prize_returned = release_prize_escrow(
+                giveaway,
+                prize_vault,
+                prize_mint,
+                creator_prize_account.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                token_program.to_account_info(),
+            )?;
+        }
+
+        let giveaway = &mut ctx.accounts.giveaway;
+        let cancelled = giveaway.transition(GiveawayStatus::Cancelled)?;
+
+        msg!("Giveaway {} cancelled by {}", giveaway.id, canceller);
+        emit_cpi!(GiveawayCancelled { giveaway_id: giveaway.id, canceller, prize_returned });
+        emit_cpi!(cancelled);
+        Ok(())
+    }
+
+    // Refunds of accepted-mint entries take [mint, mint vault, user token account] as remaining_accounts
+    pub fn claim_refund<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimRefund<'info>>) -> Result<()> {
         let giveaway = &ctx.accounts.giveaway;
         let entry = &mut ctx.accounts.entry;
 
-        require!(!giveaway.is_active, GivefiError::GiveawayStillActive);
-        require!(!giveaway.is_successful, GivefiError::GiveawayWasSuccessful);
+        require!(!giveaway.status.is_live(), GivefiError::GiveawayStillActive);
+        require!(giveaway.status.is_refundable(), GivefiError::GiveawayWasSuccessful);
         require!(entry.user == ctx.accounts.user.key(), GivefiError::UnauthorizedClaim);
         require!(!entry.claimed, GivefiError::PrizeAlreadyClaimed);
 
@@ -393,104 +1701,1876 @@ pub mod givefi {
                     transfer_instruction,
                     vault_signer,
                 );
-                anchor_lang::system_program::transfer(cpi_ctx, giveaway.entry_cost_sol)?;
+                anchor_lang::system_program::transfer(cpi_ctx, entry.amount_paid)?;
             },
             PaymentType::Give => {
+                let user_token_account = ctx.accounts.user_token_account.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+                let giveaway_token_vault = ctx.accounts.giveaway_token_vault.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+                let give_mint = ctx.accounts.give_mint.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+                let token_program = ctx.accounts.token_program.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+                require_keys_eq!(give_mint.key(), giveaway_token_vault.mint, GivefiError::InvalidTokenMint);
+                require_keys_eq!(user_token_account.owner, ctx.accounts.user.key(), GivefiError::InvalidTokenOwner);
+
+                let id_bytes = giveaway.id.to_le_bytes();
+                let seeds = &[b"giveaway", &id_bytes[..], &[giveaway.bump]];
+                let signer = &[&seeds[..]];
+
+                transfer_from_vault(
+                    token_program,
+                    &giveaway_token_vault.to_account_info(),
+                    give_mint,
+                    &user_token_account.to_account_info(),
+                    &ctx.accounts.giveaway,
+                    signer,
+                    entry.amount_paid,
+                )?;
+            },
+            PaymentType::Spl { mint_index } => {
+                let [mint_info, vault_info, user_info, ..] = ctx.remaining_accounts else {
+                    return err!(GivefiError::MintAccountsMissing);
+                };
+                let token_program = ctx.accounts.token_program.as_ref().ok_or(GivefiError::MintAccountsMissing)?;
+                let mint = refund_mint_accounts(giveaway, mint_index, mint_info, vault_info, user_info, &entry.user, ctx.program_id)?;
+
                 let id_bytes = giveaway.id.to_le_bytes();
                 let seeds = &[b"giveaway", &id_bytes[..], &[giveaway.bump]];
                 let signer = &[&seeds[..]];
+                transfer_from_vault(
+                    token_program,
+                    vault_info,
+                    &mint,
+                    user_info,
+                    &ctx.accounts.giveaway,
+                    signer,
+                    entry.amount_paid,
+                )?;
+            }
+        }
+
+        entry.claimed = true;
+        emit_cpi!(RefundPaid {
+            giveaway_id: giveaway.id,
+            user: entry.user,
+            first_entry_number: entry.first_entry_number,
+            payment_type: entry.payment_type.clone(),
+            amount: entry.amount_paid,
+            cranker: None,
+        });
+
+        let ledger = ctx.accounts.giveaway.ledger_mut(&entry.payment_type)?;
+        ledger.refunded = math::add(ledger.refunded, entry.amount_paid)?;
+        ctx.accounts.giveaway.sol_ledger.check(&ctx.accounts.giveaway_vault)?;
+        ctx.accounts.entry.close(ctx.accounts.payer.to_account_info())?;
+        release_participant(&mut ctx.accounts.participant, &ctx.accounts.payer)?;
+
+        Ok(())
+    }
+
+    // Non-winning entries of a successful giveaway have nothing left to claim, so their
+    // owners can close them and get the rent back
+    pub fn close_losing_entry(ctx: Context<CloseLosingEntry>) -> Result<()> {
+        let giveaway = &ctx.accounts.giveaway;
+        let entry = &ctx.accounts.entry;
+
+        require!(!giveaway.status.is_live(), GivefiError::GiveawayStillActive);
+        require!(
+            matches!(giveaway.status, GiveawayStatus::Drawn | GiveawayStatus::Settled),
+            GivefiError::GiveawayNotSuccessful
+        );
+        require!(giveaway.tiers_won(entry.first_entry_number, entry.count)?.is_empty(), GivefiError::EntryIsWinner);
+        // A redraw may still hand this entry a winning ticket, which needs the account to claim
+        require!(
+            !(giveaway.status == GiveawayStatus::Drawn && giveaway.redraws_unclaimed()),
+            GivefiError::RedrawPending
+        );
+        require!(entry.user == ctx.accounts.user.key(), GivefiError::UnauthorizedClaim);
+
+        msg!("Closed losing entry #{} of giveaway {}", entry.first_entry_number, giveaway.id);
+        emit_cpi!(EntryClosed {
+            giveaway_id: giveaway.id,
+            user: entry.user,
+            first_entry_number: entry.first_entry_number,
+        });
+        ctx.accounts.entry.close(ctx.accounts.payer.to_account_info())?;
+        release_participant(&mut ctx.accounts.participant, &ctx.accounts.payer)?;
+
+        Ok(())
+    }
+
+    // Bonus claims only matter while the giveaway takes entries; afterwards anyone can close one
+    // and send the rent back to whoever paid it
+    pub fn close_nft_bonus(ctx: Context<CloseNftBonus>) -> Result<()> {
+        let giveaway = &ctx.accounts.giveaway;
+        require!(!giveaway.status.is_live(), GivefiError::GiveawayStillActive);
+
+        emit_cpi!(RentReclaimed {
+            giveaway_id: giveaway.id,
+            account: ctx.accounts.nft_bonus.key(),
+            recipient: ctx.accounts.payer.key(),
+            lamports: ctx.accounts.nft_bonus.get_lamports(),
+        });
+        Ok(())
+    }
+
+    // The request is read only until the winners are drawn (or the giveaway fails)
+    pub fn close_randomness_request(ctx: Context<CloseRandomnessRequest>) -> Result<()> {
+        let giveaway = &ctx.accounts.giveaway;
+        require!(!giveaway.status.is_live(), GivefiError::GiveawayStillActive);
+
+        emit_cpi!(RentReclaimed {
+            giveaway_id: giveaway.id,
+            account: ctx.accounts.randomness_request.key(),
+            recipient: ctx.accounts.payer.key(),
+            lamports: ctx.accounts.randomness_request.get_lamports(),
+        });
+        Ok(())
+    }
+
+    // A decided or expired proposal goes back to its proposer, which also frees the PDA for a
+    // fresh vote on the same giveaway
+    pub fn close_proposal(ctx: Context<CloseProposal>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(
+            proposal.status != ProposalStatus::Voting || Clock::get()?.unix_timestamp >= proposal.voting_ends_at,
+            GivefiError::ProposalStillVoting
+        );
+
+        emit_cpi!(RentReclaimed {
+            giveaway_id: proposal.giveaway_id,
+            account: proposal.key(),
+            recipient: ctx.accounts.proposer.key(),
+            lamports: proposal.get_lamports(),
+        });
+        Ok(())
+    }
+
+    // The index is only a lookup aid for clients, so its creator may drop it at any time;
+    // create_giveaway starts a new one
+    pub fn close_creator_index(ctx: Context<CloseCreatorIndex>) -> Result<()> {
+        emit_cpi!(RentReclaimed {
+            giveaway_id: 0,
+            account: ctx.accounts.creator_index.key(),
+            recipient: ctx.accounts.creator.key(),
+            lamports: ctx.accounts.creator_index.get_lamports(),
+        });
+        Ok(())
+    }
+
+    // Anyone can push refunds for a failed giveaway. remaining_accounts holds one group per
+    // entry: [entry, owner, rent payer, owner's participant] for SOL entries, plus the owner's
+    // GIVE account for GIVE, or the mint, mint vault, the owner's token account and the mint's
+    // token program for other accepted mints.
+    pub fn crank_refunds<'info>(ctx: Context<'_, '_, 'info, 'info, CrankRefunds<'info>>) -> Result<()> {
+        let giveaway = &ctx.accounts.giveaway;
+
+        require!(!giveaway.status.is_live(), GivefiError::GiveawayStillActive);
+        require!(giveaway.status.is_refundable(), GivefiError::GiveawayWasSuccessful);
+
+        let id_bytes = giveaway.id.to_le_bytes();
+        let vault_seeds = &[b"giveaway_vault", &id_bytes[..], &[ctx.bumps.giveaway_vault]];
+        let vault_signer = &[&vault_seeds[..]];
+        let seeds = &[b"giveaway", &id_bytes[..], &[giveaway.bump]];
+        let signer = &[&seeds[..]];
+
+        let mut remaining_accounts = ctx.remaining_accounts.iter();
+        let mut refunded: u64 = 0;
+        // Booked on each vault's ledger once the giveaway can be borrowed mutably
+        let mut refunds = Vec::new();
+        while let Some(entry_info) = remaining_accounts.next() {
+            let entry: Account<'info, GiveawayEntry> = Account::try_from(entry_info)?;
+            let owner = remaining_accounts.next().ok_or(GivefiError::MissingRefundAccount)?;
+            let payer = remaining_accounts.next().ok_or(GivefiError::MissingRefundAccount)?;
+            let participant_info = remaining_accounts.next().ok_or(GivefiError::MissingRefundAccount)?;
+            let mut participant: Account<'info, Participant> = Account::try_from(participant_info)?;
+
+            let expected_entry = Pubkey::create_program_address(
+                &[b"entry", &id_bytes, &entry.first_entry_number.to_le_bytes(), &[entry.bump]],
+                ctx.program_id,
+            )
+            .map_err(|_| GivefiError::InvalidRefundEntry)?;
+            require_keys_eq!(entry_info.key(), expected_entry, GivefiError::InvalidRefundEntry);
+            require_keys_eq!(owner.key(), entry.user, GivefiError::InvalidRefundEntry);
+            require_keys_eq!(payer.key(), entry.payer, GivefiError::InvalidRentPayer);
+            let expected_participant = Pubkey::create_program_address(
+                &[b"participant", &id_bytes, entry.user.as_ref(), &[participant.bump]],
+                ctx.program_id,
+            )
+            .map_err(|_| GivefiError::InvalidRefundEntry)?;
+            require_keys_eq!(participant_info.key(), expected_participant, GivefiError::InvalidRefundEntry);
+            require!(!entry.claimed, GivefiError::PrizeAlreadyClaimed);
+
+            match entry.payment_type {
+                PaymentType::Sol => {
+                    let transfer_instruction = anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.giveaway_vault.to_account_info(),
+                        to: owner.clone(),
+                    };
+                    let cpi_ctx = CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        transfer_instruction,
+                        vault_signer,
+                    );
+                    anchor_lang::system_program::transfer(cpi_ctx, entry.amount_paid)?;
+                },
+                PaymentType::Give => {
+                    let owner_token_info = remaining_accounts.next().ok_or(GivefiError::MissingRefundAccount)?;
+                    let giveaway_token_vault =
+                        ctx.accounts.giveaway_token_vault.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+                    let give_mint = ctx.accounts.give_mint.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+                    let token_program = ctx.accounts.token_program.as_ref().ok_or(GivefiError::GiveAccountsMissing)?;
+                    require_keys_eq!(give_mint.key(), giveaway_token_vault.mint, GivefiError::InvalidTokenMint);
+                    let owner_token_account: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(owner_token_info)?;
+                    require_keys_eq!(owner_token_account.mint, give_mint.key(), GivefiError::InvalidRefundEntry);
+                    require_keys_eq!(owner_token_account.owner, entry.user, GivefiError::InvalidRefundEntry);
+
+                    transfer_from_vault(
+                        token_program,
+                        &giveaway_token_vault.to_account_info(),
+                        give_mint,
+                        owner_token_info,
+                        &ctx.accounts.giveaway,
+                        signer,
+                        entry.amount_paid,
+                    )?;
+                },
+                PaymentType::Spl { mint_index } => {
+                    let mint_info = remaining_accounts.next().ok_or(GivefiError::MissingRefundAccount)?;
+                    let vault_info = remaining_accounts.next().ok_or(GivefiError::MissingRefundAccount)?;
+                    let owner_token_info = remaining_accounts.next().ok_or(GivefiError::MissingRefundAccount)?;
+                    let program_info = remaining_accounts.next().ok_or(GivefiError::MissingRefundAccount)?;
+                    let token_program = load_token_program(program_info, mint_info)?;
+                    let mint = refund_mint_accounts(
+                        giveaway,
+                        mint_index,
+                        mint_info,
+                        vault_info,
+                        owner_token_info,
+                        &entry.user,
+                        ctx.program_id,
+                    )?;
+
+                    transfer_from_vault(
+                        &token_program,
+                        vault_info,
+                        &mint,
+                        owner_token_info,
+                        &ctx.accounts.giveaway,
+                        signer,
+                        entry.amount_paid,
+                    )?;
+                }
+            }
+
+            emit_cpi!(RefundPaid {
+                giveaway_id: giveaway.id,
+                user: entry.user,
+                first_entry_number: entry.first_entry_number,
+                payment_type: entry.payment_type.clone(),
+                amount: entry.amount_paid,
+                cranker: Some(ctx.accounts.cranker.key()),
+            });
+            refunds.push((entry.payment_type.clone(), entry.amount_paid));
+
+            let bounty = ctx.accounts.program_state.crank_bounty_lamports.min(entry_info.lamports());
+            let entry_lamports = math::sub(entry_info.lamports(), bounty)?;
+            let cranker_lamports = math::add(ctx.accounts.cranker.lamports(), bounty)?;
+            **entry_info.try_borrow_mut_lamports()? = entry_lamports;
+            **ctx.accounts.cranker.try_borrow_mut_lamports()? = cranker_lamports;
+            entry.close(payer.clone())?;
+            // Written back now, since a later group may hold the same wallet's next entry
+            release_participant(&mut participant, payer)?;
+            participant.exit(ctx.program_id)?;
+            refunded = math::add(refunded, 1)?;
+        }
+
+        msg!("Cranked {} refunds for giveaway {}", refunded, giveaway.id);
+        let giveaway = &mut ctx.accounts.giveaway;
+        for (payment_type, amount) in &refunds {
+            let ledger = giveaway.ledger_mut(payment_type)?;
+            ledger.refunded = math::add(ledger.refunded, *amount)?;
+        }
+        giveaway.sol_ledger.check(&ctx.accounts.giveaway_vault)?;
+        Ok(())
+    }
+
+    // Sweeps what each vault's ledger doesn't account for (stray transfers, rounding dust) to the
+    // treasury. Refundable giveaways keep what they still owe. A settled giveaway still holds the
+    // pool slices no jackpot claim took (winners who chose the prize, or no jackpot option); those
+    // are split like a jackpot, with the winner's part going to the creator who funded the prize.
+    // Once the SOL vault owes nothing more, the creator also gets its rent reserve back and the
+    // vault is left empty. Token vaults go the same way: remaining_accounts holds [mint, vault,
+    // creator's token account, treasury's token account, token program] for the GIVE vault when
+    // GIVE is accepted, then for each accepted mint in order.
+    pub fn reconcile_vault<'info>(ctx: Context<'_, '_, 'info, 'info, ReconcileVault<'info>>) -> Result<()> {
+        let giveaway = &ctx.accounts.giveaway;
+
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_CLAIMS), GivefiError::ProgramPaused);
+        require!(!giveaway.frozen, GivefiError::GiveawayFrozen);
+        require!(
+            giveaway.status == GiveawayStatus::Settled || giveaway.status.is_refundable(),
+            GivefiError::GiveawayNotSettled
+        );
+
+        let sol = giveaway.reconciliation(
+            &giveaway.sol_ledger,
+            giveaway.sol_pool()?,
+            ctx.accounts.giveaway_vault.lamports(),
+        )?;
+        let reserve = if giveaway.status == GiveawayStatus::Settled || giveaway.sol_ledger.held()? == 0 {
+            giveaway.sol_ledger.reserve
+        } else {
+            0
+        };
+        let creator_amount = math::add(sol.creator_amount()?, reserve)?;
+        let treasury_amount = sol.treasury_amount()?;
+        let mut reconciled = creator_amount > 0 || treasury_amount > 0;
+
+        let id_bytes = giveaway.id.to_le_bytes();
+        let vault_seeds = &[b"giveaway_vault", &id_bytes[..], &[ctx.bumps.giveaway_vault]];
+        let vault_signer = &[&vault_seeds[..]];
+        for (destination, amount) in [
+            (ctx.accounts.creator.to_account_info(), creator_amount),
+            (ctx.accounts.treasury_wallet.to_account_info(), treasury_amount),
+        ] {
+            if amount > 0 {
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.giveaway_vault.to_account_info(),
+                        to: destination,
+                    },
+                    vault_signer,
+                );
+                anchor_lang::system_program::transfer(cpi_ctx, amount)?;
+            }
+        }
+        if reconciled {
+            emit_cpi!(VaultReconciled {
+                giveaway_id: giveaway.id,
+                mint: None,
+                treasury_wallet: ctx.accounts.treasury_wallet.key(),
+                dust: sol.dust,
+                surplus: sol.surplus,
+                reserve,
+                creator_amount,
+                treasury_amount: sol.unpaid.treasury,
+            });
+        }
+
+        let seeds = &[b"giveaway", &id_bytes[..], &[giveaway.bump]];
+        let signer = &[&seeds[..]];
+        let token_vaults = giveaway
+            .entry_cost_give
+            .map(|_| PaymentType::Give)
+            .into_iter()
+            .chain((0..giveaway.accepted_mints.len()).map(|index| PaymentType::Spl { mint_index: index as u8 }));
+        let mut vault_accounts = ctx.remaining_accounts.chunks(5);
+        // Booked on each vault's ledger once the giveaway can be borrowed mutably
+        let mut token_reconciliations = Vec::new();
+        for payment_type in token_vaults {
+            let Some([mint_info, vault_info, creator_info, treasury_info, program_info]) = vault_accounts.next() else {
+                return err!(match payment_type {
+                    PaymentType::Give => GivefiError::GiveAccountsMissing,
+                    _ => GivefiError::MintAccountsMissing,
+                });
+            };
+            let expected_vault = match payment_type {
+                PaymentType::Spl { mint_index } => {
+                    mint_vault_address(giveaway.id, &giveaway.accepted_mints[mint_index as usize].mint, ctx.program_id)
+                }
+                _ => Pubkey::find_program_address(&[b"token_vault", &id_bytes], ctx.program_id).0,
+            };
+            require_keys_eq!(vault_info.key(), expected_vault, GivefiError::InvalidMintVault);
+            let vault: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(vault_info)?;
+            let mint = load_mint(mint_info, &vault.mint)?;
+            let token_program = load_token_program(program_info, mint_info)?;
+
+            let ledger = giveaway.ledger(&payment_type)?;
+            let reconciliation = giveaway.reconciliation(ledger, ledger.collected, vault.amount)?;
+            let creator_amount = reconciliation.creator_amount()?;
+            let treasury_amount = reconciliation.treasury_amount()?;
+            if creator_amount == 0 && treasury_amount == 0 {
+                continue;
+            }
+            check_token_account(creator_info, &vault.mint, &giveaway.authority)?;
+            check_token_account(treasury_info, &vault.mint, &ctx.accounts.program_state.treasury_wallet)?;
+            for (destination, amount) in [(creator_info, creator_amount), (treasury_info, treasury_amount)] {
+                transfer_from_vault(&token_program, vault_info, &mint, destination, giveaway, signer, amount)?;
+            }
+
+            emit_cpi!(VaultReconciled {
+                giveaway_id: giveaway.id,
+                mint: Some(vault.mint),
+                treasury_wallet: ctx.accounts.treasury_wallet.key(),
+                dust: reconciliation.dust,
+                surplus: reconciliation.surplus,
+                reserve: 0,
+                creator_amount,
+                treasury_amount: reconciliation.unpaid.treasury,
+            });
+            token_reconciliations.push((payment_type, reconciliation));
+            reconciled = true;
+        }
+        require!(reconciled, GivefiError::NothingToReconcile);
+
+        let giveaway = &mut ctx.accounts.giveaway;
+        for (payment_type, reconciliation) in std::iter::once((PaymentType::Sol, sol)).chain(token_reconciliations) {
+            giveaway.ledger_mut(&payment_type)?.book_reconciliation(&reconciliation)?;
+        }
+        giveaway.sol_ledger.reserve = math::sub(giveaway.sol_ledger.reserve, reserve)?;
+        giveaway.sol_ledger.check(&ctx.accounts.giveaway_vault)?;
+        Ok(())
+    }
+
+    // Read-only views: the result is returned as Borsh return data, for CPI callers and simulation
+    pub fn get_giveaway_summary(ctx: Context<ViewGiveaway>) -> Result<GiveawaySummary> {
+        Ok(ctx.accounts.giveaway.summary(Clock::get()?.unix_timestamp))
+    }
+
+    pub fn get_pool_totals(ctx: Context<ViewGiveaway>) -> Result<Vec<CurrencyPool>> {
+        ctx.accounts.giveaway.pools()
+    }
+
+    // A wallet without a Participant account holds no tickets
+    pub fn get_wallet_odds(ctx: Context<ViewWalletOdds>, wallet: Pubkey) -> Result<WalletOdds> {
+        let tickets = ctx.accounts.participant.as_ref().map_or(0, |participant| participant.tickets);
+        Ok(ctx.accounts.giveaway.wallet_odds(wallet, tickets))
+    }
+
+    pub fn get_payout_breakdown(ctx: Context<ViewGiveaway>) -> Result<PayoutBreakdown> {
+        ctx.accounts.giveaway.payout_breakdown()
+    }
+}
+
+// Counts one of the wallet's entries as closed, and closes its Participant account back to the
+// rent payer once none is left
+fn release_participant<'info>(participant: &mut Account<'info, Participant>, payer: &AccountInfo<'info>) -> Result<()> {
+    participant.entries = math::sub(participant.entries, 1)?;
+    if participant.entries == 0 {
+        participant.close(payer.clone())?;
+    }
+    Ok(())
+}
+
+// Makes room for one more id in the creator's index, topping up its rent from `payer`
+fn grow_creator_index<'info>(
+    creator_index: &Account<'info, CreatorIndex>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let info = creator_index.to_account_info();
+    let new_len = CreatorIndex::space(creator_index.giveaway_ids.len() + 1);
+    let shortfall = Rent::get()?.minimum_balance(new_len).saturating_sub(info.lamports());
+    if shortfall > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer { from: payer.to_account_info(), to: info.clone() },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, shortfall)?;
+    }
+    info.realloc(new_len, false)?;
+    Ok(())
+}
+
+// SlotHashes is too large to deserialize on-chain, so read the newest entry straight from
+// the raw layout: u64 length prefix followed by (u64 slot, [u8; 32] hash) pairs, newest first.
+fn latest_slot_hash(slot_hashes: &AccountInfo) -> Result<(u64, [u8; 32])> {
+    let data = slot_hashes.try_borrow_data()?;
+    require!(data.len() >= 8 + 8 + 32, GivefiError::SlotHashUnavailable);
+
+    let len = u64::from_le_bytes(data[0..8].try_into().unwrap());
+    require!(len > 0, GivefiError::SlotHashUnavailable);
+
+    let slot = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    let mut slot_hash = [0u8; 32];
+    slot_hash.copy_from_slice(&data[16..48]);
+    Ok((slot, slot_hash))
+}
+
+// Hash of the first produced slot at or after `slot` (a skipped slot has none), or None once it
+// has aged out of SlotHashes. Only meaningful after `slot` has passed.
+fn draw_slot_hash(slot_hashes: &AccountInfo, slot: u64) -> Result<Option<[u8; 32]>> {
+    let data = slot_hashes.try_borrow_data()?;
+    require!(data.len() >= 8, GivefiError::SlotHashUnavailable);
+
+    let len = u64::from_le_bytes(data[0..8].try_into().unwrap()) as usize;
+    let mut found = None;
+    for entry in data[8..].chunks_exact(40).take(len) {
+        let entry_slot = u64::from_le_bytes(entry[0..8].try_into().unwrap());
+        if entry_slot < slot {
+            return Ok(found);
+        }
+        found = Some(entry[8..40].try_into().unwrap());
+        if entry_slot == slot {
+            return Ok(found);
+        }
+    }
+    Ok(None)
+}
+
+// Sends whatever is left in the prize vault to `destination` and closes the vault
+fn release_prize_escrow<'info>(
+    giveaway: &Account<'info, Giveaway>,
+    prize_vault: &InterfaceAccount<'info, TokenAccount>,
+    prize_mint: &InterfaceAccount<'info, Mint>,
+    destination: AccountInfo<'info>,
+    rent_destination: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<u64> {
+    let id_bytes = giveaway.id.to_le_bytes();
+    let seeds = &[b"giveaway", &id_bytes[..], &[giveaway.bump]];
+    let signer = &[&seeds[..]];
+
+    let remaining = prize_vault.amount;
+    if remaining > 0 {
+        let transfer_instruction = TransferChecked {
+            from: prize_vault.to_account_info(),
+            mint: prize_mint.to_account_info(),
+            to: destination,
+            authority: giveaway.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.clone(),
+            transfer_instruction,
+            signer,
+        );
+        token_interface::transfer_checked(cpi_ctx, remaining, prize_mint.decimals)?;
+    }
+
+    // Token-2022 won't close an account holding withheld transfer fees, so sweep them to the mint.
+    // Only mints with the transfer-fee extension accept the harvest.
+    if has_transfer_fee(&prize_mint.to_account_info())? {
+        let harvest_instruction = token_interface::HarvestWithheldTokensToMint {
+            token_program_id: token_program.clone(),
+            mint: prize_mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program.clone(), harvest_instruction);
+        token_interface::harvest_withheld_tokens_to_mint(cpi_ctx, vec![prize_vault.to_account_info()])?;
+    }
+
+    let close_instruction = token_interface::CloseAccount {
+        account: prize_vault.to_account_info(),
+        destination: rent_destination,
+        authority: giveaway.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program,
+        close_instruction,
+        signer,
+    );
+    token_interface::close_account(cpi_ctx)?;
+
+    Ok(remaining)
+}
+
+fn has_transfer_fee(mint: &AccountInfo) -> Result<bool> {
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(false);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(mint.get_extension::<TransferFeeConfig>().is_ok())
+}
+
+// Vault transfers don't forward a hook program's extra accounts, so hooked mints can't be paid out
+fn has_transfer_hook(mint: &AccountInfo) -> Result<bool> {
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(false);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(mint.get_extension::<TransferHook>().is_ok())
+}
+
+fn entry_from_seed(seed: &[u8; 32], entries: u64) -> u64 {
+    u64::from_le_bytes(seed[0..8].try_into().unwrap()) % entries
+}
+
+// Draws `count` distinct entry numbers from one seed. Each pick re-hashes the seed with its
+// index and a retry nonce, so collisions are resampled instead of shifting to a neighbour.
+fn draw_winners(seed: &[u8; 32], entries: u64, count: usize) -> Vec<u64> {
+    let mut winners: Vec<u64> = Vec::with_capacity(count);
+    for index in 0..count as u64 {
+        let mut nonce: u64 = 0;
+        loop {
+            let pick_seed = hashv(&[seed, &index.to_le_bytes(), &nonce.to_le_bytes()]).to_bytes();
+            let candidate = entry_from_seed(&pick_seed, entries);
+            if !winners.contains(&candidate) {
+                winners.push(candidate);
+                break;
+            }
+            nonce += 1;
+        }
+    }
+    winners
+}
+
+pub fn mint_vault_address(giveaway_id: u64, mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"mint_vault", &giveaway_id.to_le_bytes(), mint.as_ref()], program_id).0
+}
+
+fn check_token_account<'info>(info: &'info AccountInfo<'info>, mint: &Pubkey, owner: &Pubkey) -> Result<()> {
+    let token_account: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(info)?;
+    require_keys_eq!(token_account.mint, *mint, GivefiError::InvalidTokenMint);
+    require_keys_eq!(token_account.owner, *owner, GivefiError::InvalidTokenOwner);
+    Ok(())
+}
+
+fn load_mint<'info>(info: &'info AccountInfo<'info>, expected: &Pubkey) -> Result<InterfaceAccount<'info, Mint>> {
+    require_keys_eq!(info.key(), *expected, GivefiError::InvalidTokenMint);
+    InterfaceAccount::try_from(info)
+}
+
+// The token program passed alongside a mint in remaining_accounts; it has to be the mint's owner
+fn load_token_program<'info>(
+    info: &'info AccountInfo<'info>,
+    mint_info: &AccountInfo<'info>,
+) -> Result<Interface<'info, TokenInterface>> {
+    require_keys_eq!(info.key(), *mint_info.owner, GivefiError::InvalidTokenProgram);
+    Interface::try_from(info)
+}
+
+// Validates the mint, vault and destination of an accepted-mint refund and returns the mint
+fn refund_mint_accounts<'info>(
+    giveaway: &Giveaway,
+    mint_index: u8,
+    mint_info: &'info AccountInfo<'info>,
+    vault_info: &AccountInfo<'info>,
+    user_info: &'info AccountInfo<'info>,
+    user: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<InterfaceAccount<'info, Mint>> {
+    let accepted = giveaway.accepted_mints.get(mint_index as usize).ok_or(GivefiError::MintNotAccepted)?;
+    let mint = load_mint(mint_info, &accepted.mint)?;
+    require_keys_eq!(
+        vault_info.key(),
+        mint_vault_address(giveaway.id, &accepted.mint, program_id),
+        GivefiError::InvalidMintVault
+    );
+    check_token_account(user_info, &accepted.mint, user)?;
+    Ok(mint)
+}
+
+// Pays `amount` out of a vault owned by the giveaway PDA; zero amounts are skipped
+fn transfer_from_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    vault: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    destination: &AccountInfo<'info>,
+    giveaway: &Account<'info, Giveaway>,
+    signer: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let transfer_instruction = TransferChecked {
+        from: vault.clone(),
+        mint: mint.to_account_info(),
+        to: destination.clone(),
+        authority: giveaway.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        transfer_instruction,
+        signer,
+    );
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+// Pays `amount` from a user into one of the giveaway's vaults and returns what the vault
+// actually received, which is less than `amount` when the mint withholds a transfer fee
+fn deposit_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    vault: &mut InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    authority: &Signer<'info>,
+    amount: u64,
+) -> Result<u64> {
+    let balance_before = vault.amount;
+    let transfer_instruction = TransferChecked {
+        from: from.to_account_info(),
+        mint: mint.to_account_info(),
+        to: vault.to_account_info(),
+        authority: authority.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(
+        token_program.to_account_info(),
+        transfer_instruction,
+    );
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)?;
+
+    vault.reload()?;
+    math::sub(vault.amount, balance_before)
+}
+
+// Returns the member's ticket allowance (0 when uncapped); fails when the giveaway is allowlisted
+// and the signer can't prove membership
+fn check_allowlist(giveaway: &Giveaway, user: &Pubkey, allowlist_proof: Option<&AllowlistProof>) -> Result<u64> {
+    let Some(root) = giveaway.allowlist_root else {
+        return Ok(0);
+    };
+    let allowlist_proof = allowlist_proof.ok_or(GivefiError::AllowlistProofRequired)?;
+    require!(
+        allowlist::verify(&root, allowlist::leaf(user, allowlist_proof.allowance), &allowlist_proof.proof),
+        GivefiError::NotOnAllowlist
+    );
+    Ok(allowlist_proof.allowance)
+}
+
+// Checks the signer against the giveaway's holder gate and returns the bonus tickets earned by
+// the NFTs in `bonus_accounts`. Each bonus NFT is marked with a claim PDA so it only counts once.
+fn check_entry_gate<'info>(
+    giveaway: &Giveaway,
+    user: &Signer<'info>,
+    gate_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    gate_metadata: Option<&Account<'info, MetadataAccount>>,
+    bonus_accounts: &'info [AccountInfo<'info>],
+    system_program: &Program<'info, System>,
+    program_id: &Pubkey,
+) -> Result<u64> {
+    let Some(gate) = &giveaway.entry_gate else {
+        return Ok(0);
+    };
+    let holding = gate_token_account.ok_or(GivefiError::GateAccountsMissing)?;
+    require_keys_eq!(holding.owner, user.key(), GivefiError::EntryGateNotMet);
+
+    match gate {
+        EntryGate::Token { mint, min_balance } => {
+            require_keys_eq!(holding.mint, *mint, GivefiError::EntryGateNotMet);
+            require!(holding.amount >= *min_balance, GivefiError::EntryGateNotMet);
+            Ok(0)
+        },
+        EntryGate::Collection { collection, bonus_tickets_per_nft } => {
+            let metadata = gate_metadata.ok_or(GivefiError::GateAccountsMissing)?;
+            require!(holds_collection_nft(holding, metadata, collection), GivefiError::EntryGateNotMet);
+
+            let id_bytes = giveaway.id.to_le_bytes();
+            let mut bonus_tickets = 0;
+            for accounts in bonus_accounts.chunks(3) {
+                let [token_info, metadata_info, claim_info] = accounts else {
+                    return err!(GivefiError::GateAccountsMissing);
+                };
+                let token_account: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(token_info)?;
+                let metadata: Account<'info, MetadataAccount> = Account::try_from(metadata_info)?;
+                require_keys_eq!(token_account.owner, user.key(), GivefiError::EntryGateNotMet);
+                require!(holds_collection_nft(&token_account, &metadata, collection), GivefiError::EntryGateNotMet);
+
+                let (claim_key, claim_bump) = Pubkey::find_program_address(
+                    &[b"nft_bonus", &id_bytes, token_account.mint.as_ref()],
+                    program_id,
+                );
+                require_keys_eq!(claim_info.key(), claim_key, GivefiError::InvalidBonusClaim);
+                require!(claim_info.owner != program_id, GivefiError::NftBonusAlreadyClaimed);
+
+                let claim_seeds = &[b"nft_bonus", &id_bytes[..], token_account.mint.as_ref(), &[claim_bump]];
+                let bonus = NftBonus { giveaway_id: giveaway.id, mint: token_account.mint, payer: user.key() };
+                init_nft_bonus(claim_info, &[&claim_seeds[..]], &bonus, user, system_program, program_id)?;
+
+                bonus_tickets = math::add(bonus_tickets, *bonus_tickets_per_nft as u64)?;
+            }
+            Ok(bonus_tickets)
+        }
+    }
+}
+
+// Creates an NFT's bonus claim. Lamports sent to the PDA beforehand are kept and only the
+// shortfall is paid: a plain create_account would fail on them and lock the NFT's bonus for good.
+fn init_nft_bonus<'info>(
+    claim_info: &'info AccountInfo<'info>,
+    claim_signer: &[&[&[u8]]],
+    bonus: &NftBonus,
+    user: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let space = 8 + NftBonus::INIT_SPACE;
+    let shortfall = Rent::get()?.minimum_balance(space).saturating_sub(claim_info.lamports());
+    if shortfall > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer { from: user.to_account_info(), to: claim_info.clone() },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, shortfall)?;
+    }
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        anchor_lang::system_program::Allocate { account_to_allocate: claim_info.clone() },
+        claim_signer,
+    );
+    anchor_lang::system_program::allocate(cpi_ctx, space as u64)?;
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        anchor_lang::system_program::Assign { account_to_assign: claim_info.clone() },
+        claim_signer,
+    );
+    anchor_lang::system_program::assign(cpi_ctx, program_id)?;
+
+    bonus.try_serialize(&mut &mut claim_info.try_borrow_mut_data()?[..])
+}
+
+fn holds_collection_nft(token_account: &TokenAccount, metadata: &MetadataAccount, collection: &Pubkey) -> bool {
+    token_account.amount == 1
+        && metadata.mint == token_account.mint
+        && metadata.collection.as_ref().is_some_and(|nft_collection| nft_collection.verified && nft_collection.key == *collection)
+}
+
+fn validate_prize_tiers(prize_tiers: &[PrizeTier], min_participants: u64) -> Result<()> {
+    require!(prize_tiers.len() <= MAX_PRIZE_TIERS, GivefiError::TooManyPrizeTiers);
+
+    let mut total_winners: u64 = 0;
+    let mut total_share_bps: u64 = 0;
+    for tier in prize_tiers {
+        require!(tier.count > 0, GivefiError::InvalidPrizeTier);
+        require!(tier.description.len() <= MAX_TIER_DESCRIPTION_LEN, GivefiError::DescriptionTooLong);
+        total_winners = math::add(total_winners, tier.count as u64)?;
+        total_share_bps = math::add(total_share_bps, tier.share_bps as u64)?;
+    }
+
+    require!(total_winners as usize <= MAX_WINNERS, GivefiError::TooManyWinners);
+    // Winners are drawn without replacement, so a successful giveaway must have enough entries
+    require!(total_winners <= min_participants, GivefiError::TooManyWinners);
+    require!(total_share_bps == BPS_DENOMINATOR, GivefiError::InvalidPrizeShares);
+    Ok(())
+}
+
+// One winner's portion of a pool: the tier's share divided evenly among its winners
+pub fn tier_slice(total: u64, tier: &PrizeTier) -> Result<u64> {
+    math::mul_div(total, tier.share_bps as u64, BPS_DENOMINATOR * tier.count as u64)
+}
+
+// What an entry's winning tickets release from a pool: one slice of each tier in `won`
+pub fn tiers_slice(total: u64, prize_tiers: &[PrizeTier], won: &[usize]) -> Result<u64> {
+    won.iter().try_fold(0, |slice, &tier| math::add(slice, tier_slice(total, &prize_tiers[tier])?))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct JackpotShares {
+    pub slice: u64,
+    pub winner: u64,
+    pub creator: u64,
+    pub treasury: u64,
+}
+
+// How a jackpot claim pays out one pool: the winning tickets' slice, split three ways. A slashed
+// commitment forfeits the creator's part to the treasury.
+pub fn jackpot_shares(
+    total: u64,
+    prize_tiers: &[PrizeTier],
+    won: &[usize],
+    split: &JackpotSplit,
+    commitment_slashed: bool,
+) -> Result<JackpotShares> {
+    split_slice(tiers_slice(total, prize_tiers, won)?, split, commitment_slashed)
+}
+
+pub fn split_slice(slice: u64, split: &JackpotSplit, commitment_slashed: bool) -> Result<JackpotShares> {
+    let (winner, mut creator, mut treasury) = split.apply(slice)?;
+    if commitment_slashed {
+        treasury = math::add(treasury, creator)?;
+        creator = 0;
+    }
+    Ok(JackpotShares { slice, winner, creator, treasury })
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct JackpotSplit {
+    pub winner_bps: u16,
+    pub creator_bps: u16,
+    pub treasury_bps: u16,
+}
+
+impl JackpotSplit {
+    pub fn validate(&self, program_state: &ProgramState) -> Result<()> {
+        let total_bps = self.winner_bps as u64 + self.creator_bps as u64 + self.treasury_bps as u64;
+        require!(total_bps == BPS_DENOMINATOR, GivefiError::InvalidJackpotSplit);
+        require!(self.treasury_bps <= program_state.max_treasury_fee_bps, GivefiError::TreasuryFeeTooHigh);
+        require!(self.winner_bps >= program_state.min_winner_share_bps, GivefiError::WinnerShareTooLow);
+        Ok(())
+    }
+
+    // Returns (winner, creator, treasury). The treasury takes whatever the integer divisions
+    // leave behind, so the three amounts always add up to `total`.
+    pub fn apply(&self, total: u64) -> Result<(u64, u64, u64)> {
+        let winner = math::mul_div(total, self.winner_bps as u64, BPS_DENOMINATOR)?;
+        let creator = math::mul_div(total, self.creator_bps as u64, BPS_DENOMINATOR)?;
+        Ok((winner, creator, math::sub(total, math::add(winner, creator)?)?))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum UnclaimedPolicy {
+    // Draw replacement winners from tickets that haven't won; goes to the creator once none are left
+    Redraw,
+    Creator,
+    Treasury,
+    Charity { wallet: Pubkey },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct ClaimDeadline {
+    // Counted from the draw, or from the latest redraw
+    pub window_secs: i64,
+    pub policy: UnclaimedPolicy,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum EntryGate {
+    // Holders of at least `min_balance` of `mint`
+    Token { mint: Pubkey, min_balance: u64 },
+    // Holders of an NFT from the verified `collection`; each extra NFT shown earns bonus tickets
+    Collection { collection: Pubkey, bonus_tickets_per_nft: u8 },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct AllowlistProof {
+    pub allowance: u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Debug)]
+pub struct PrizeTier {
+    pub count: u8,
+    pub share_bps: u16,
+    #[max_len(MAX_TIER_DESCRIPTION_LEN)]
+    pub description: String,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum ProposalKind {
+    // Lets a PendingApproval giveaway open
+    Approve,
+    // Calls off a live giveaway
+    Cancel,
+}
+
+impl ProposalKind {
+    // Each kind has its own PDA, so a cancellation vote can run after the approval vote
+    pub fn seed(self) -> &'static [u8] {
+        match self {
+            ProposalKind::Approve => b"proposal",
+            ProposalKind::Cancel => b"cancel_proposal",
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ProposalStatus {
+    Voting,
+    Approved,
+    Rejected,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum GiveawayStatus {
+    // Waiting for the prize escrow and/or DAO approval
+    PendingApproval,
+    Open,
+    // Past end_timestamp or ended early; no more entries
+    Closed,
+    AwaitingRandomness,
+    Drawn,
+    // Every winning ticket has been claimed
+    Settled,
+    // Too few participants; entries are refundable
+    Failed,
+    Cancelled,
+}
+
+impl GiveawayStatus {
+    pub fn can_transition_to(self, next: GiveawayStatus) -> bool {
+        use GiveawayStatus::*;
+        matches!(
+            (self, next),
+            (PendingApproval, Open | Failed | Cancelled)
+                | (Open, Closed | Cancelled)
+                | (Closed, AwaitingRandomness | Drawn | Failed | Cancelled)
+                | (AwaitingRandomness, Drawn | Cancelled)
+                | (Drawn, Settled)
+        )
+    }
+
+    // Not yet drawn, failed or cancelled
+    pub fn is_live(self) -> bool {
+        matches!(
+            self,
+            GiveawayStatus::PendingApproval
+                | GiveawayStatus::Open
+                | GiveawayStatus::Closed
+                | GiveawayStatus::AwaitingRandomness
+        )
+    }
+
+    pub fn is_refundable(self) -> bool {
+        matches!(self, GiveawayStatus::Failed | GiveawayStatus::Cancelled)
+    }
+
+    // Winning tickets can be claimed or forfeited only while a claim can still settle the giveaway
+    pub fn is_claimable(self) -> bool {
+        self.can_transition_to(GiveawayStatus::Settled)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum PaymentType {
+    Sol,
+    Give,
+    // Paid in `Giveaway.accepted_mints[mint_index]`
+    Spl { mint_index: u8 },
+}
+
+// Running totals for one vault, in lamports or in base units of its mint; anything it holds
+// beyond `held()` is unaccounted for
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Default, Debug)]
+pub struct VaultLedger {
+    pub collected: u64,
+    // Jackpot shares sent to winners and the creator
+    pub paid_out: u64,
+    pub refunded: u64,
+    // Treasury shares, plus rounding dust swept by reconcile_vault
+    pub fees: u64,
+    // Lamports that arrived outside of entries, swept to the treasury by reconcile_vault
+    pub swept: u64,
+    // Rent-exempt minimum the creator deposited in the SOL vault at creation; not part of the pool
+    pub reserve: u64,
+}
+
+impl VaultLedger {
+    pub fn held(&self) -> Result<u64> {
+        math::sub(self.collected, math::add(math::add(self.paid_out, self.refunded)?, self.fees)?)
+    }
+
+    // A jackpot claim's slice leaves the vault: winner and creator parts as payouts, the
+    // treasury's as fees
+    pub fn book_jackpot(&mut self, shares: &JackpotShares) -> Result<()> {
+        self.paid_out = math::add(self.paid_out, math::add(shares.winner, shares.creator)?)?;
+        self.fees = math::add(self.fees, shares.treasury)?;
+        Ok(())
+    }
+
+    pub fn book_reconciliation(&mut self, reconciliation: &Reconciliation) -> Result<()> {
+        let unpaid = &reconciliation.unpaid;
+        self.paid_out = math::add(self.paid_out, math::add(unpaid.winner, unpaid.creator)?)?;
+        self.fees = math::add(self.fees, math::add(unpaid.treasury, reconciliation.dust)?)?;
+        self.swept = math::add(self.swept, reconciliation.surplus)?;
+        Ok(())
+    }
+
+    // The vault must always cover what the ledger says it still holds, on top of its reserve
+    pub fn check(&self, vault: &AccountInfo) -> Result<()> {
+        require!(vault.lamports() >= math::add(self.held()?, self.reserve)?, GivefiError::VaultInvariantViolated);
+        Ok(())
+    }
+}
+
+// What reconcile_vault takes out of one vault on top of its ledger
+pub struct Reconciliation {
+    // Pool slices no jackpot claim took, split like a jackpot
+    pub unpaid: JackpotShares,
+    // Rounding left over once every winner's slice is counted
+    pub dust: u64,
+    // Whatever arrived outside of entries
+    pub surplus: u64,
+}
+
+impl Reconciliation {
+    // Winners' and creator's parts of the unpaid slices, both paid to the creator
+    pub fn creator_amount(&self) -> Result<u64> {
+        math::add(self.unpaid.winner, self.unpaid.creator)
+    }
+
+    pub fn treasury_amount(&self) -> Result<u64> {
+        math::add(math::add(self.unpaid.treasury, self.dust)?, self.surplus)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct TicketRange {
+    pub first_entry_number: u64,
+    pub count: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct AcceptedMint {
+    pub mint: Pubkey,
+    pub price: u64,
+    // Paid tickets bought with this mint
+    pub entries: u64,
+    // Amount actually received, net of any transfer fee withheld by the mint
+    pub pool: u64,
+    pub ledger: VaultLedger,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeProgram<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ProgramState::INIT_SPACE,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateProgramState<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump = program_state.bump,
+        has_one = authority @ GivefiError::Unauthorized
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(giveaway_id: Option<u64>)]
+pub struct CreateGiveaway<'info> {
+    // Ahead of the giveaway, whose seeds may come from its counter
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Giveaway::INIT_SPACE,
+        seeds = [b"giveaway", program_state.giveaway_id_for(giveaway_id)?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    /// CHECK: This is a PDA that will be used as a vault to hold SOL for the giveaway.
+    /// It is derived from seeds and its address is verified through the PDA derivation.
+    #[account(
+        mut,
+        seeds = [b"giveaway_vault", program_state.giveaway_id_for(giveaway_id)?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub giveaway_vault: UncheckedAccount<'info>,
+    // GIVE vault, created only for giveaways that accept GIVE
+    #[account(
+        init,
+        payer = authority,
+        token::mint = give_mint,
+        token::authority = giveaway,
+        token::token_program = token_program,
+        seeds = [b"token_vault", program_state.giveaway_id_for(giveaway_id)?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub giveaway_token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub give_mint: Option<InterfaceAccount<'info, Mint>>,
+    // Grown by one id per giveaway in the handler
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = CreatorIndex::space(0),
+        seeds = [b"creator_index", authority.key().as_ref()],
+        bump
+    )]
+    pub creator_index: Account<'info, CreatorIndex>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FundPrize<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump,
+        has_one = authority @ GivefiError::InvalidOwner
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        init,
+        payer = authority,
+        token::mint = prize_mint,
+        token::authority = giveaway,
+        seeds = [b"prize_vault", giveaway.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub prize_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        constraint = giveaway.prize_mint == Some(prize_mint.key()) @ GivefiError::InvalidPrizeMint
+    )]
+    pub prize_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = prize_mint,
+        token::authority = authority
+    )]
+    pub authority_prize_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddEntryMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump,
+        has_one = authority @ GivefiError::InvalidOwner
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = giveaway,
+        seeds = [b"mint_vault", giveaway.id.to_le_bytes().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub mint_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FreezeGiveaway<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump,
+        has_one = authority @ GivefiError::Unauthorized
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump = program_state.bump,
+        constraint = program_state.pending_authority == Some(new_authority.key()) @ GivefiError::NotPendingAuthority
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub new_authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureDao<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump = program_state.bump,
+        has_one = authority @ GivefiError::Unauthorized
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + DaoConfig::INIT_SPACE,
+        seeds = [b"dao_config"],
+        bump
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeGiveaway<'info> {
+    #[account(
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump,
+        has_one = authority @ GivefiError::InvalidOwner
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [b"proposal", giveaway.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeCancellation<'info> {
+    #[account(
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        init,
+        payer = member,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [b"cancel_proposal", giveaway.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+    #[account(mut)]
+    pub member: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        mut,
+        seeds = [proposal.kind.seed(), giveaway.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+    pub member: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EnterGiveawaySol<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + GiveawayEntry::INIT_SPACE,
+        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), giveaway.current_entries.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, GiveawayEntry>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Participant::INIT_SPACE,
+        seeds = [b"participant", giveaway.id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub participant: Account<'info, Participant>,
+    /// CHECK: This is a PDA that serves as a vault to hold SOL for the giveaway.
+    /// It is derived from seeds and receives SOL transfers from users entering the giveaway.
+    #[account(
+        mut,
+        seeds = [b"giveaway_vault", giveaway.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub giveaway_vault: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    // Holder gate accounts, required only when the giveaway sets an entry gate
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub gate_metadata: Option<Account<'info, MetadataAccount>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EnterGiveawayGive<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + GiveawayEntry::INIT_SPACE,
+        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), giveaway.current_entries.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, GiveawayEntry>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Participant::INIT_SPACE,
+        seeds = [b"participant", giveaway.id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        token::mint = give_mint,
+        token::authority = user
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"token_vault", giveaway.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub giveaway_token_vault: InterfaceAccount<'info, TokenAccount>,
+    pub give_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    // Holder gate accounts, required only when the giveaway sets an entry gate
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub gate_metadata: Option<Account<'info, MetadataAccount>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EnterGiveawayBulk<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + GiveawayEntry::INIT_SPACE,
+        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), giveaway.current_entries.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, GiveawayEntry>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Participant::INIT_SPACE,
+        seeds = [b"participant", giveaway.id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub participant: Account<'info, Participant>,
+    /// CHECK: This is a PDA that serves as a vault to hold SOL for the giveaway.
+    /// It is derived from seeds and receives SOL transfers from users entering the giveaway.
+    #[account(
+        mut,
+        seeds = [b"giveaway_vault", giveaway.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub giveaway_vault: UncheckedAccount<'info>,
+    // GIVE accounts, required only when paying in GIVE
+    #[account(mut)]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"token_vault", giveaway.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub giveaway_token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    // Vault of the accepted mint, required only when paying with one
+    #[account(mut)]
+    pub mint_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    // Mint being paid with, GIVE or the accepted mint; required for any token payment
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    // Holder gate accounts, required only when the giveaway sets an entry gate
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub gate_metadata: Option<Account<'info, MetadataAccount>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
 
-                let transfer_instruction = Transfer {
-                    from: ctx.accounts.giveaway_token_vault.to_account_info(),
-                    to: ctx.accounts.user_token_account.to_account_info(),
-                    authority: ctx.accounts.giveaway.to_account_info(),
-                };
-                let cpi_ctx = CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    transfer_instruction,
-                    signer,
-                );
-                token::transfer(cpi_ctx, giveaway.entry_cost_give.unwrap())?;
-            }
-        }
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EndRaffleEarly<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    pub authority: Signer<'info>,
+}
 
-        entry.claimed = true;
-        ctx.accounts.entry.close(ctx.accounts.treasury_wallet.to_account_info())?;
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestRandomness<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RandomnessRequest::INIT_SPACE,
+        seeds = [b"randomness_request", giveaway.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub randomness_request: Account<'info, RandomnessRequest>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: This is the SlotHashes sysvar, verified by the address constraint.
+    /// It is read manually because the full sysvar is too large to deserialize on-chain.
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-        Ok(())
-    }
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleRandomness<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        seeds = [b"randomness_request", giveaway.id.to_le_bytes().as_ref()],
+        bump = randomness_request.bump
+    )]
+    pub randomness_request: Account<'info, RandomnessRequest>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub authority: Signer<'info>,
+    /// CHECK: This is the SlotHashes sysvar, verified by the address constraint.
+    /// It is read manually because the full sysvar is too large to deserialize on-chain.
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
 
-    pub fn get_giveaway_info(ctx: Context<GetGiveawayInfo>) -> Result<()> {
-        let giveaway = &ctx.accounts.giveaway;
-        msg!("Giveaway {} Info:", giveaway.id);
-        Ok(())
-    }
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordDrawSlot<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    /// CHECK: This is the SlotHashes sysvar, verified by the address constraint.
+    /// It is read manually because the full sysvar is too large to deserialize on-chain.
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
-pub enum PaymentType {
-    Sol,
-    Give,
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FulfillRandomness<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        mut,
+        seeds = [b"randomness_request", giveaway.id.to_le_bytes().as_ref()],
+        bump = randomness_request.bump
+    )]
+    pub randomness_request: Account<'info, RandomnessRequest>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    /// Must be the oracle program's authority PDA, so only that program can sign via CPI.
+    pub oracle_authority: Signer<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct InitializeProgram<'info> {
+pub struct DrawWinner<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
     #[account(
-        init,
-        payer = authority,
-        space = 8 + ProgramState::INIT_SPACE,
         seeds = [b"program_state"],
-        bump
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub authority: Signer<'info>,
+    /// CHECK: This is the SlotHashes sysvar, verified by the address constraint.
+    /// It is read manually because the full sysvar is too large to deserialize on-chain.
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimPrize<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        mut,
+        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), entry.first_entry_number.to_le_bytes().as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Account<'info, GiveawayEntry>,
+    // Closed back to the rent payer along with the wallet's last entry
+    #[account(
+        mut,
+        seeds = [b"participant", giveaway.id.to_le_bytes().as_ref(), entry.user.as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Account<'info, Participant>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// CHECK: Receives the entry's rent back; must be the payer recorded on the entry.
+    #[account(
+        mut,
+        address = entry.payer @ GivefiError::InvalidRentPayer
+    )]
+    pub payer: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump
     )]
     pub program_state: Account<'info, ProgramState>,
+    // Escrow accounts, required only when the giveaway has an on-chain prize
+    #[account(
+        mut,
+        seeds = [b"prize_vault", giveaway.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub prize_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::authority = user
+    )]
+    pub user_prize_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub prize_mint: Option<InterfaceAccount<'info, Mint>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReclaimPrize<'info> {
+    #[account(
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump,
+        has_one = authority @ GivefiError::InvalidOwner
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        mut,
+        seeds = [b"prize_vault", giveaway.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub prize_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = prize_vault.mint,
+        token::authority = authority
+    )]
+    pub authority_prize_account: InterfaceAccount<'info, TokenAccount>,
+    // Writable so Token-2022 can harvest withheld transfer fees into it before the vault closes
+    #[account(
+        mut,
+        address = prize_vault.mint @ GivefiError::InvalidPrizeMint
+    )]
+    pub prize_mint: InterfaceAccount<'info, Mint>,
     #[account(mut)]
     pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(giveaway_id: u64)]
-pub struct CreateGiveaway<'info> {
+pub struct CancelGiveaway<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = 8 + Giveaway::INIT_SPACE,
-        seeds = [b"giveaway", giveaway_id.to_le_bytes().as_ref()],
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub canceller: Signer<'info>,
+    /// CHECK: This account must be the giveaway creator, who receives the prize vault rent.
+    /// It is validated through the address constraint.
+    #[account(
+        mut,
+        address = giveaway.authority @ GivefiError::InvalidOwner
+    )]
+    pub creator: UncheckedAccount<'info>,
+    // Escrow accounts, required only when the giveaway has a funded on-chain prize
+    #[account(
+        mut,
+        seeds = [b"prize_vault", giveaway.id.to_le_bytes().as_ref()],
         bump
     )]
+    pub prize_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub creator_prize_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub prize_mint: Option<InterfaceAccount<'info, Mint>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimJackpot<'info> {
+    #[account(
+        mut,
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
     pub giveaway: Account<'info, Giveaway>,
     #[account(
         mut,
+        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), entry.first_entry_number.to_le_bytes().as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Account<'info, GiveawayEntry>,
+    // Closed back to the rent payer along with the wallet's last entry
+    #[account(
+        mut,
+        seeds = [b"participant", giveaway.id.to_le_bytes().as_ref(), entry.user.as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Account<'info, Participant>,
+    #[account(
         seeds = [b"program_state"],
         bump = program_state.bump
     )]
     pub program_state: Account<'info, ProgramState>,
-    /// CHECK: This is a PDA that will be used as a vault to hold SOL for the giveaway.
-    /// It is derived from seeds and its address is verified through the PDA derivation.
+    /// CHECK: This is a PDA that serves as a vault holding SOL for the giveaway.
+    /// It is derived from seeds and SOL is transferred from it during jackpot claims.
     #[account(
         mut,
-        seeds = [b"giveaway_vault", giveaway_id.to_le_bytes().as_ref()],
+        seeds = [b"giveaway_vault", giveaway.id.to_le_bytes().as_ref()],
         bump
     )]
     pub giveaway_vault: UncheckedAccount<'info>,
+    // GIVE accounts, required only when the giveaway collected GIVE
+    #[account(mut)]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
-        init,
-        payer = authority,
-        token::mint = give_mint,
-        token::authority = giveaway,
-        seeds = [b"token_vault", giveaway_id.to_le_bytes().as_ref()],
+        mut,
+        seeds = [b"token_vault", giveaway.id.to_le_bytes().as_ref()],
         bump
     )]
-    pub giveaway_token_vault: Account<'info, TokenAccount>,
-    pub give_mint: Account<'info, token::Mint>,
+    pub giveaway_token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub give_mint: Option<InterfaceAccount<'info, Mint>>,
     #[account(mut)]
-    pub authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    pub user: Signer<'info>,
+    /// CHECK: This account must be the giveaway authority/owner who receives a portion of the jackpot.
+    /// It is validated through the constraint below to ensure security.
+    #[account(
+        mut,
+        constraint = owner.key() == giveaway.authority @ GivefiError::InvalidOwner
+    )]
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: This account is validated to match the treasury_wallet stored in program_state.
+    /// The address constraint ensures this is the correct treasury wallet.
+    #[account(
+        mut,
+        address = program_state.treasury_wallet
+    )]
+    pub treasury_wallet: UncheckedAccount<'info>,
+    /// CHECK: Receives the entry's rent back; must be the payer recorded on the entry.
+    #[account(
+        mut,
+        address = entry.payer @ GivefiError::InvalidRentPayer
+    )]
+    pub payer: UncheckedAccount<'info>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct EnterGiveawaySol<'info> {
+pub struct ClaimRefund<'info> {
     #[account(
         mut,
         seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
@@ -498,137 +3578,201 @@ pub struct EnterGiveawaySol<'info> {
     )]
     pub giveaway: Account<'info, Giveaway>,
     #[account(
-        init,
-        payer = user,
-        space = 8 + GiveawayEntry::INIT_SPACE,
-        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), giveaway.current_entries.to_le_bytes().as_ref()],
-        bump
+        mut,
+        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), entry.first_entry_number.to_le_bytes().as_ref()],
+        bump = entry.bump
     )]
     pub entry: Account<'info, GiveawayEntry>,
-    /// CHECK: This is a PDA that serves as a vault to hold SOL for the giveaway.
-    /// It is derived from seeds and receives SOL transfers from users entering the giveaway.
+    // Closed back to the rent payer along with the wallet's last entry
+    #[account(
+        mut,
+        seeds = [b"participant", giveaway.id.to_le_bytes().as_ref(), entry.user.as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Account<'info, Participant>,
+    /// CHECK: This is a PDA that serves as a vault holding SOL for the giveaway.
+    /// It is derived from seeds and SOL is refunded from it when giveaway fails.
     #[account(
         mut,
         seeds = [b"giveaway_vault", giveaway.id.to_le_bytes().as_ref()],
         bump
     )]
     pub giveaway_vault: UncheckedAccount<'info>,
+    // GIVE accounts, required only to refund a GIVE entry
+    #[account(mut)]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"token_vault", giveaway.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub giveaway_token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub give_mint: Option<InterfaceAccount<'info, Mint>>,
     #[account(mut)]
     pub user: Signer<'info>,
+    /// CHECK: Receives the entry's rent back; must be the payer recorded on the entry.
+    #[account(
+        mut,
+        address = entry.payer @ GivefiError::InvalidRentPayer
+    )]
+    pub payer: UncheckedAccount<'info>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct EnterGiveawayGive<'info> {
+pub struct CloseLosingEntry<'info> {
     #[account(
-        mut,
         seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
         bump = giveaway.bump
     )]
     pub giveaway: Account<'info, Giveaway>,
     #[account(
-        init,
-        payer = user,
-        space = 8 + GiveawayEntry::INIT_SPACE,
-        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), giveaway.current_entries.to_le_bytes().as_ref()],
-        bump
+        mut,
+        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), entry.first_entry_number.to_le_bytes().as_ref()],
+        bump = entry.bump
     )]
     pub entry: Account<'info, GiveawayEntry>,
+    // Closed back to the rent payer along with the wallet's last entry
     #[account(
         mut,
-        token::mint = give_mint,
-        token::authority = user
+        seeds = [b"participant", giveaway.id.to_le_bytes().as_ref(), entry.user.as_ref()],
+        bump = participant.bump
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub participant: Account<'info, Participant>,
+    /// CHECK: Receives the entry's rent back; must be the payer recorded on the entry.
     #[account(
         mut,
-        seeds = [b"token_vault", giveaway.id.to_le_bytes().as_ref()],
-        bump
+        address = entry.payer @ GivefiError::InvalidRentPayer
     )]
-    pub giveaway_token_vault: Account<'info, TokenAccount>,
-    pub give_mint: Account<'info, token::Mint>,
-    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
     pub user: Signer<'info>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct EndRaffleEarly<'info> {
+pub struct CloseNftBonus<'info> {
     #[account(
-        mut,
         seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
         bump = giveaway.bump
     )]
     pub giveaway: Account<'info, Giveaway>,
-    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"nft_bonus", giveaway.id.to_le_bytes().as_ref(), nft_bonus.mint.as_ref()],
+        bump
+    )]
+    pub nft_bonus: Account<'info, NftBonus>,
+    /// CHECK: Receives the claim's rent back; must be the payer recorded on it.
+    #[account(
+        mut,
+        address = nft_bonus.payer @ GivefiError::InvalidRentPayer
+    )]
+    pub payer: UncheckedAccount<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct RequestRandomness<'info> {
+pub struct CloseRandomnessRequest<'info> {
     #[account(
-        mut,
         seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
         bump = giveaway.bump
     )]
     pub giveaway: Account<'info, Giveaway>,
-    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"randomness_request", giveaway.id.to_le_bytes().as_ref()],
+        bump = randomness_request.bump
+    )]
+    pub randomness_request: Account<'info, RandomnessRequest>,
+    /// CHECK: Receives the request's rent back; must be the payer recorded on it.
+    #[account(
+        mut,
+        address = randomness_request.payer @ GivefiError::InvalidRentPayer
+    )]
+    pub payer: UncheckedAccount<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct SettleRandomness<'info> {
+pub struct CloseProposal<'info> {
     #[account(
-        mut,
         seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
         bump = giveaway.bump
     )]
     pub giveaway: Account<'info, Giveaway>,
-    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [proposal.kind.seed(), giveaway.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: Receives the proposal's rent back; must be the proposal's proposer.
+    #[account(
+        mut,
+        address = proposal.proposer @ GivefiError::InvalidRentPayer
+    )]
+    pub proposer: UncheckedAccount<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct DrawWinner<'info> {
+pub struct CloseCreatorIndex<'info> {
     #[account(
         mut,
-        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
-        bump = giveaway.bump
+        close = creator,
+        seeds = [b"creator_index", creator.key().as_ref()],
+        bump = creator_index.bump
     )]
-    pub giveaway: Account<'info, Giveaway>,
-    pub authority: Signer<'info>,
+    pub creator_index: Account<'info, CreatorIndex>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ClaimPrize<'info> {
+pub struct CrankRefunds<'info> {
     #[account(
         mut,
         seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
         bump = giveaway.bump
     )]
     pub giveaway: Account<'info, Giveaway>,
+    /// CHECK: This is a PDA that serves as a vault holding SOL for the giveaway.
+    /// It is derived from seeds and SOL is refunded from it when giveaway fails.
     #[account(
         mut,
-        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), entry.entry_number.to_le_bytes().as_ref()],
-        bump = entry.bump
+        seeds = [b"giveaway_vault", giveaway.id.to_le_bytes().as_ref()],
+        bump
     )]
-    pub entry: Account<'info, GiveawayEntry>,
-    #[account(mut)]
-    pub user: Signer<'info>,
-    /// CHECK: This account is validated to match the treasury_wallet stored in program_state.
-    /// The address constraint ensures this is the correct treasury wallet.
+    pub giveaway_vault: UncheckedAccount<'info>,
+    // GIVE accounts, required only when the batch holds GIVE entries
     #[account(
         mut,
-        address = program_state.treasury_wallet
+        seeds = [b"token_vault", giveaway.id.to_le_bytes().as_ref()],
+        bump
     )]
-    pub treasury_wallet: UncheckedAccount<'info>,
+    pub giveaway_token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub give_mint: Option<InterfaceAccount<'info, Mint>>,
     #[account(
         seeds = [b"program_state"],
         bump = program_state.bump
     )]
     pub program_state: Account<'info, ProgramState>,
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    // Required only when the batch holds token entries
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ClaimJackpot<'info> {
+pub struct ReclaimUnclaimed<'info> {
     #[account(
         mut,
         seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
@@ -637,82 +3781,86 @@ pub struct ClaimJackpot<'info> {
     pub giveaway: Account<'info, Giveaway>,
     #[account(
         mut,
-        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), entry.entry_number.to_le_bytes().as_ref()],
+        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), entry.first_entry_number.to_le_bytes().as_ref()],
         bump = entry.bump
     )]
     pub entry: Account<'info, GiveawayEntry>,
+    // Closed back to the rent payer along with the wallet's last entry
+    #[account(
+        mut,
+        seeds = [b"participant", giveaway.id.to_le_bytes().as_ref(), entry.user.as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Account<'info, Participant>,
     #[account(
         seeds = [b"program_state"],
         bump = program_state.bump
     )]
     pub program_state: Account<'info, ProgramState>,
     /// CHECK: This is a PDA that serves as a vault holding SOL for the giveaway.
-    /// It is derived from seeds and SOL is transferred from it during jackpot claims.
+    /// It is derived from seeds and a forfeited jackpot share is transferred from it.
     #[account(
         mut,
         seeds = [b"giveaway_vault", giveaway.id.to_le_bytes().as_ref()],
         bump
     )]
     pub giveaway_vault: UncheckedAccount<'info>,
+    /// CHECK: Receives a forfeited share; checked against the giveaway's unclaimed policy.
+    /// Not needed when the winners are redrawn.
+    #[account(mut)]
+    pub recipient: Option<UncheckedAccount<'info>>,
+    // Escrow accounts, required only when a forfeited share includes an on-chain prize
     #[account(
         mut,
-        token::mint = give_mint,
-        token::authority = user
-    )]
-    pub user_token_account: Account<'info, TokenAccount>,
-    #[account(
-        mut,
-        token::mint = give_mint,
-        token::authority = owner
-    )]
-    pub owner_token_account: Account<'info, TokenAccount>,
-    #[account(
-        mut,
-        token::mint = give_mint
-    )]
-    pub treasury_token_account: Account<'info, TokenAccount>,
-    #[account(
-        mut,
-        seeds = [b"token_vault", giveaway.id.to_le_bytes().as_ref()],
+        seeds = [b"prize_vault", giveaway.id.to_le_bytes().as_ref()],
         bump
     )]
-    pub giveaway_token_vault: Account<'info, TokenAccount>,
-    pub give_mint: Account<'info, token::Mint>,
+    pub prize_vault: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(mut)]
-    pub user: Signer<'info>,
-    /// CHECK: This account must be the giveaway authority/owner who receives a portion of the jackpot.
-    /// It is validated through the constraint below to ensure security.
-    #[account(
-        mut,
-        constraint = owner.key() == giveaway.authority @ GivefiError::InvalidOwner
-    )]
-    pub owner: UncheckedAccount<'info>,
-    /// CHECK: This account is validated to match the treasury_wallet stored in program_state.
-    /// The address constraint ensures this is the correct treasury wallet.
+    pub recipient_prize_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub prize_mint: Option<InterfaceAccount<'info, Mint>>,
+    /// CHECK: Receives the entry's rent back; must be the payer recorded on the entry.
     #[account(
         mut,
-        address = program_state.treasury_wallet
+        address = entry.payer @ GivefiError::InvalidRentPayer
     )]
-    pub treasury_wallet: UncheckedAccount<'info>,
-    pub token_program: Program<'info, Token>,
+    pub payer: UncheckedAccount<'info>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ClaimRefund<'info> {
+pub struct RedrawWinners<'info> {
     #[account(
+        mut,
         seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
         bump = giveaway.bump
     )]
     pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        seeds = [b"program_state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub authority: Signer<'info>,
+    /// CHECK: This is the SlotHashes sysvar, verified by the address constraint.
+    /// It is read manually because the full sysvar is too large to deserialize on-chain.
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReconcileVault<'info> {
     #[account(
         mut,
-        seeds = [b"entry", giveaway.id.to_le_bytes().as_ref(), entry.entry_number.to_le_bytes().as_ref()],
-        bump = entry.bump
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
     )]
-    pub entry: Account<'info, GiveawayEntry>,
+    pub giveaway: Account<'info, Giveaway>,
     /// CHECK: This is a PDA that serves as a vault holding SOL for the giveaway.
-    /// It is derived from seeds and SOL is refunded from it when giveaway fails.
+    /// It is derived from seeds and only the surplus over its ledger is swept from it.
     #[account(
         mut,
         seeds = [b"giveaway_vault", giveaway.id.to_le_bytes().as_ref()],
@@ -720,43 +3868,49 @@ pub struct ClaimRefund<'info> {
     )]
     pub giveaway_vault: UncheckedAccount<'info>,
     #[account(
-        mut,
-        token::mint = give_mint,
-        token::authority = user
-    )]
-    pub user_token_account: Account<'info, TokenAccount>,
-    #[account(
-        mut,
-        seeds = [b"token_vault", giveaway.id.to_le_bytes().as_ref()],
-        bump
+        seeds = [b"program_state"],
+        bump = program_state.bump
     )]
-    pub giveaway_token_vault: Account<'info, TokenAccount>,
-    pub give_mint: Account<'info, token::Mint>,
-    #[account(mut)]
-    pub user: Signer<'info>,
-    /// CHECK: This account is validated to match the treasury_wallet stored in program_state.
-    /// The address constraint ensures this is the correct treasury wallet.
+    pub program_state: Account<'info, ProgramState>,
+    /// CHECK: This is the treasury wallet that receives the swept surplus.
+    /// It is verified against the program state.
     #[account(
         mut,
         address = program_state.treasury_wallet
     )]
     pub treasury_wallet: UncheckedAccount<'info>,
+    /// CHECK: This account must be the giveaway creator, who receives its share of unpaid pool slices.
+    /// It is validated through the address constraint.
     #[account(
-        seeds = [b"program_state"],
-        bump = program_state.bump
+        mut,
+        address = giveaway.authority @ GivefiError::InvalidOwner
     )]
-    pub program_state: Account<'info, ProgramState>,
-    pub token_program: Program<'info, Token>,
+    pub creator: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct GetGiveawayInfo<'info> {
+pub struct ViewGiveaway<'info> {
+    #[account(
+        seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
+        bump = giveaway.bump
+    )]
+    pub giveaway: Account<'info, Giveaway>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct ViewWalletOdds<'info> {
     #[account(
         seeds = [b"giveaway", giveaway.id.to_le_bytes().as_ref()],
         bump = giveaway.bump
     )]
     pub giveaway: Account<'info, Giveaway>,
+    #[account(
+        seeds = [b"participant", giveaway.id.to_le_bytes().as_ref(), wallet.as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Option<Account<'info, Participant>>,
 }
 
 #[account]
@@ -765,9 +3919,105 @@ pub struct ProgramState {
     pub authority: Pubkey,
     pub treasury_wallet: Pubkey,
     pub total_giveaways: u64,
+    pub oracle_program: Pubkey,
+    pub max_treasury_fee_bps: u16,
+    pub min_winner_share_bps: u16,
+    pub dao_approval_required: bool,
+    pub pending_authority: Option<Pubkey>,
+    // Incremented by every admin instruction that changes this account
+    pub config_changes: u64,
+    pub paused: u8,
+    // Paid to whoever cranks a refund, out of the closed entry's rent before the rest goes back
+    // to the payer; 0 turns the bounty off
+    pub crank_bounty_lamports: u64,
+    pub bump: u8,
+}
+
+impl ProgramState {
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.paused & flag != 0
+    }
+
+    // The id create_giveaway will use: the caller's, or the next one off the counter
+    pub fn giveaway_id_for(&self, requested: Option<u64>) -> Result<u64> {
+        requested.map_or_else(|| math::add(AUTO_GIVEAWAY_ID_BASE, self.total_giveaways), Ok)
+    }
+}
+
+// Every giveaway a wallet has created, oldest first, so clients can discover them from the
+// creator's key alone
+#[account]
+pub struct CreatorIndex {
+    pub creator: Pubkey,
+    pub giveaway_ids: Vec<u64>,
+    pub bump: u8,
+}
+
+impl CreatorIndex {
+    pub fn space(giveaways: usize) -> usize {
+        8 + 32 + 4 + 8 * giveaways + 1
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DaoConfig {
+    #[max_len(MAX_DAO_MEMBERS)]
+    pub members: Vec<Pubkey>,
+    // Approving votes needed for a proposal to pass
+    pub quorum: u8,
+    pub voting_period_secs: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub giveaway_id: u64,
+    pub kind: ProposalKind,
+    pub proposer: Pubkey,
+    pub created_at: i64,
+    pub voting_ends_at: i64,
+    pub votes_for: u8,
+    pub votes_against: u8,
+    #[max_len(MAX_DAO_MEMBERS)]
+    pub voters: Vec<Pubkey>,
+    pub status: ProposalStatus,
     pub bump: u8,
 }
 
+impl Proposal {
+    fn open(
+        &mut self,
+        giveaway_id: u64,
+        kind: ProposalKind,
+        proposer: Pubkey,
+        now: i64,
+        voting_period_secs: i64,
+    ) -> Result<()> {
+        self.giveaway_id = giveaway_id;
+        self.kind = kind;
+        self.proposer = proposer;
+        self.created_at = now;
+        self.voting_ends_at = now.checked_add(voting_period_secs).ok_or(GivefiError::MathOverflow)?;
+        self.votes_for = 0;
+        self.votes_against = 0;
+        self.voters = Vec::new();
+        self.status = ProposalStatus::Voting;
+        Ok(())
+    }
+}
+
+// Marks an NFT's bonus tickets as used in one giveaway
+#[account]
+#[derive(InitSpace)]
+pub struct NftBonus {
+    pub giveaway_id: u64,
+    pub mint: Pubkey,
+    // Paid the rent, and gets it back through close_nft_bonus
+    pub payer: Pubkey,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Giveaway {
@@ -777,29 +4027,329 @@ pub struct Giveaway {
     pub entry_cost_give: Option<u64>,
     pub max_entries: u64,
     pub min_participants: u64,
+    // Cap on tickets a single wallet may hold, tallied on its Participant account
+    pub max_entries_per_wallet: Option<u64>,
+    // Merkle root of the member list; when set, entries must prove membership
+    pub allowlist_root: Option<[u8; 32]>,
+    pub entry_gate: Option<EntryGate>,
     pub current_entries: u64,
     pub sol_entries: u64,
     pub give_entries: u64,
+    // GIVE actually received, net of any transfer fee withheld by the mint
+    pub give_pool: u64,
+    pub give_ledger: VaultLedger,
+    #[max_len(MAX_ACCEPTED_MINTS)]
+    pub accepted_mints: Vec<AcceptedMint>,
+    pub sol_ledger: VaultLedger,
     #[max_len(100)]
     pub prize_description: String,
+    pub prize_mint: Option<Pubkey>,
+    pub prize_amount: u64,
+    pub prize_funded: bool,
+    pub dao_approved: bool,
+    pub status: GiveawayStatus,
+    pub frozen: bool,
     pub end_timestamp: i64,
     pub jackpot_option_enabled: bool,
+    pub jackpot_split: JackpotSplit,
     pub early_end_enabled: bool,
-    pub is_active: bool,
-    pub is_successful: bool,
-    pub winner: Option<u64>,
-    pub prize_claimed: bool,
-    pub jackpot_claimed: bool,
-    pub randomness_requested: bool,
+    #[max_len(MAX_PRIZE_TIERS)]
+    pub prize_tiers: Vec<PrizeTier>,
+    // Winning entry numbers in draw order; the first tier's winners come first
+    #[max_len(MAX_WINNERS)]
+    pub winners: Vec<u64>,
+    // When each winner's claim window closes, by position in `winners`
+    #[max_len(MAX_WINNERS)]
+    pub winner_deadlines: Vec<i64>,
+    pub prizes_claimed: u8,
+    pub jackpots_claimed: u8,
+    // Winning tickets whose share reclaim_unclaimed routed away
+    pub forfeited: u8,
+    // Tickets of winning entries closed while a Redraw giveaway is drawn; redraws skip them
+    #[max_len(MAX_RETIRED_ENTRIES)]
+    pub retired_tickets: Vec<TicketRange>,
+    // Positions in `winners` forfeited to a redraw, waiting for the hash of `redraw_slot`
+    #[max_len(MAX_WINNERS)]
+    pub pending_redraws: Vec<u8>,
+    pub redraw_slot: u64,
+    pub claim_deadline: Option<ClaimDeadline>,
+    // When the winners were drawn
+    pub drawn_at: i64,
+    pub randomness_commitment: [u8; 32],
+    // Future slot whose hash seeds the reveal or fallback draw, and that hash once read
+    pub recorded_slot: u64,
+    pub recorded_slot_hash: [u8; 32],
+    // Whether request_randomness went to an oracle rather than the authority's reveal
+    pub oracle_requested: bool,
+    pub commitment_slashed: bool,
+    pub bump: u8,
+}
+
+impl Giveaway {
+    pub fn total_winners(&self) -> usize {
+        self.prize_tiers.iter().map(|tier| tier.count as usize).sum()
+    }
+
+    // Lamports collected from SOL tickets
+    pub fn sol_pool(&self) -> Result<u64> {
+        math::mul(self.sol_entries, self.entry_cost_sol)
+    }
+
+    // Ledger of the vault that entries paid with `payment_type` went to
+    pub fn ledger(&self, payment_type: &PaymentType) -> Result<&VaultLedger> {
+        Ok(match payment_type {
+            PaymentType::Sol => &self.sol_ledger,
+            PaymentType::Give => &self.give_ledger,
+            PaymentType::Spl { mint_index } => {
+                &self.accepted_mints.get(*mint_index as usize).ok_or(GivefiError::MintNotAccepted)?.ledger
+            }
+        })
+    }
+
+    pub fn ledger_mut(&mut self, payment_type: &PaymentType) -> Result<&mut VaultLedger> {
+        Ok(match payment_type {
+            PaymentType::Sol => &mut self.sol_ledger,
+            PaymentType::Give => &mut self.give_ledger,
+            PaymentType::Spl { mint_index } => {
+                &mut self.accepted_mints.get_mut(*mint_index as usize).ok_or(GivefiError::MintNotAccepted)?.ledger
+            }
+        })
+    }
+
+    // Splits what a vault holding `balance` has beyond `ledger`. Refundable giveaways keep what
+    // they still owe; a settled one also gives up the slices of `pool` no jackpot claim took.
+    pub fn reconciliation(&self, ledger: &VaultLedger, pool: u64, balance: u64) -> Result<Reconciliation> {
+        let held = ledger.held()?;
+        let floor = math::add(held, ledger.reserve)?;
+        require!(balance >= floor, GivefiError::VaultInvariantViolated);
+        let surplus = math::sub(balance, floor)?;
+
+        let mut unpaid = JackpotShares { slice: 0, winner: 0, creator: 0, treasury: 0 };
+        let mut dust = 0;
+        if self.status == GiveawayStatus::Settled {
+            // Every winner's slice added up; the pool beyond that is rounding dust, under one
+            // base unit per winner
+            let slices = self.prize_tiers.iter().try_fold(0, |slices, tier| {
+                math::add(slices, math::mul(tier_slice(pool, tier)?, tier.count as u64)?)
+            })?;
+            dust = held.min(math::sub(pool, slices)?);
+            unpaid = split_slice(math::sub(held, dust)?, &self.jackpot_split, self.commitment_slashed)?;
+        }
+        Ok(Reconciliation { unpaid, dust, surplus })
+    }
+
+    // What a jackpot claim takes from `pool` for the winning tickets in tiers `won`: each releases
+    // its tier's slice, which is then split under this giveaway's terms
+    pub fn claim_shares(&self, pool: u64, won: &[usize]) -> Result<JackpotShares> {
+        jackpot_shares(pool, &self.prize_tiers, won, &self.jackpot_split, self.commitment_slashed)
+    }
+
+    // Hash of the pinned draw slot, stored the first time it is read so it outlives SlotHashes;
+    // None once it aged out without being read
+    pub fn pinned_slot_hash(&mut self, slot_hashes: &AccountInfo, now_slot: u64) -> Result<Option<[u8; 32]>> {
+        if self.recorded_slot_hash != [0u8; 32] {
+            return Ok(Some(self.recorded_slot_hash));
+        }
+        require!(now_slot > self.recorded_slot, GivefiError::SlotHashNotReady);
+        let slot_hash = draw_slot_hash(slot_hashes, self.recorded_slot)?;
+        if let Some(slot_hash) = slot_hash {
+            self.recorded_slot_hash = slot_hash;
+        }
+        Ok(slot_hash)
+    }
+
+    // Positions in `winners` of every winning ticket in `first_entry_number..first_entry_number + count`
+    pub fn winning_positions(&self, first_entry_number: u64, count: u64) -> Result<Vec<usize>> {
+        let tickets = first_entry_number..math::add(first_entry_number, count)?;
+        Ok(self
+            .winners
+            .iter()
+            .enumerate()
+            .filter(|(_, winner)| tickets.contains(winner))
+            .map(|(position, _)| position)
+            .collect())
+    }
+
+    // Prize tier of every winning ticket in `first_entry_number..first_entry_number + count`
+    pub fn tiers_won(&self, first_entry_number: u64, count: u64) -> Result<Vec<usize>> {
+        Ok(self
+            .winning_positions(first_entry_number, count)?
+            .into_iter()
+            .filter_map(|position| self.tier_at(position))
+            .collect())
+    }
+
+    // Stores a fresh draw; every winner's claim window starts now
+    pub fn record_winners(&mut self, winners: Vec<u64>, now: i64) {
+        self.winner_deadlines = vec![self.claim_deadline_from(now); winners.len()];
+        self.winners = winners;
+        self.drawn_at = now;
+    }
+
+    // Giveaways without a deadline can be claimed forever
+    pub fn claim_deadline_from(&self, now: i64) -> i64 {
+        self.claim_deadline.map_or(i64::MAX, |deadline| now.saturating_add(deadline.window_secs))
+    }
+
+    // An entry's window stays open while any of its winning `positions` is within its deadline
+    pub fn claim_window_closed(&self, positions: &[usize], now: i64) -> bool {
+        self.claim_deadline.is_some()
+            && positions
+                .iter()
+                .all(|&position| self.winner_deadlines.get(position).is_none_or(|&deadline| now >= deadline))
+    }
+
+    pub fn redraws_unclaimed(&self) -> bool {
+        self.claim_deadline.is_some_and(|deadline| deadline.policy == UnclaimedPolicy::Redraw)
+    }
+
+    // Records the tickets of a winning entry that is being closed, so a redraw can't hand one of
+    // them out. Returns false when there is nothing to record or no room left; redraws need the
+    // entry recorded, so once the list is full they stop and later ranges don't matter.
+    pub fn retire_tickets(&mut self, first_entry_number: u64, count: u64) -> bool {
+        if !self.redraws_unclaimed() || self.retired_tickets.len() >= MAX_RETIRED_ENTRIES {
+            return false;
+        }
+        self.retired_tickets.push(TicketRange { first_entry_number, count });
+        true
+    }
+
+    // Tickets a redraw can't pick, as sorted, non-overlapping `start..end` ranges: the current
+    // winners and every retired entry
+    fn redraw_exclusions(&self) -> Vec<(u64, u64)> {
+        let mut ranges: Vec<(u64, u64)> = self
+            .winners
+            .iter()
+            .map(|&ticket| (ticket, ticket + 1))
+            .chain(self.retired_tickets.iter().map(|range| (range.first_entry_number, range.first_entry_number + range.count)))
+            .collect();
+        ranges.sort_unstable();
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        merged
+    }
+
+    // Number of tickets a redraw can pick from
+    pub fn redraw_pool(&self) -> u64 {
+        let excluded: u64 = self.redraw_exclusions().iter().map(|(start, end)| end - start).sum();
+        self.current_entries.saturating_sub(excluded)
+    }
+
+    // The `n`-th ticket, counting from zero, that a redraw can pick; `n` must be below `redraw_pool`
+    pub fn nth_redraw_ticket(&self, n: u64) -> u64 {
+        let mut ticket = n;
+        for (start, end) in self.redraw_exclusions() {
+            if start > ticket {
+                break;
+            }
+            ticket += end - start;
+        }
+        ticket
+    }
+
+    // Index of the prize tier the `position`-th drawn winner belongs to
+    fn tier_at(&self, position: usize) -> Option<usize> {
+        let mut upper = 0;
+        self.prize_tiers.iter().position(|tier| {
+            upper += tier.count as usize;
+            position < upper
+        })
+    }
+
+    // The only way `status` changes; rejects moves the lifecycle doesn't allow and returns the
+    // event for the caller to emit
+    pub fn transition(&mut self, next: GiveawayStatus) -> Result<GiveawayStatusChanged> {
+        require!(self.status.can_transition_to(next), GivefiError::InvalidStatusTransition);
+        let event = GiveawayStatusChanged { giveaway_id: self.id, from: self.status, to: next };
+        self.status = next;
+        Ok(event)
+    }
+
+    // Opens entries once the prize is escrowed and the DAO (if any) has approved
+    fn open_if_ready(&mut self) -> Result<Option<GiveawayStatusChanged>> {
+        if self.status == GiveawayStatus::PendingApproval && self.prize_funded && self.dao_approved {
+            return self.transition(GiveawayStatus::Open).map(Some);
+        }
+        Ok(None)
+    }
+
+    // Nothing fires at end_timestamp, so the first instruction to notice records the close
+    fn close_if_ended(&mut self, now: i64) -> Result<Option<GiveawayStatusChanged>> {
+        if self.status == GiveawayStatus::Open && now >= self.end_timestamp {
+            return self.transition(GiveawayStatus::Closed).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn settle_if_done(&mut self) -> Result<Option<GiveawayStatusChanged>> {
+        if self.prizes_claimed as usize + self.jackpots_claimed as usize + self.forfeited as usize == self.total_winners() {
+            return self.transition(GiveawayStatus::Settled).map(Some);
+        }
+        Ok(None)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RandomnessRequest {
+    pub giveaway_id: u64,
+    pub oracle_program: Pubkey,
+    pub seed: [u8; 32],
+    pub request_slot: u64,
+    pub fulfilled: bool,
+    pub result: [u8; 32],
+    // Paid the rent, and gets it back through close_randomness_request
+    pub payer: Pubkey,
+    pub bump: u8,
+}
+
+// One per wallet and giveaway, so a wallet's tickets can be looked up without scanning entries
+#[account]
+#[derive(InitSpace)]
+pub struct Participant {
+    pub giveaway_id: u64,
+    pub user: Pubkey,
+    pub tickets: u64,
+    pub entries: u64,
     pub bump: u8,
 }
 
+impl Participant {
+    // `allowance` is the wallet's allowlist ticket allowance, 0 when it has none
+    pub fn add_tickets(&mut self, giveaway: &Giveaway, user: Pubkey, bump: u8, quantity: u64, allowance: u64) -> Result<()> {
+        let tickets = math::add(self.tickets, quantity)?;
+        if let Some(cap) = giveaway.max_entries_per_wallet {
+            require!(tickets <= cap, GivefiError::WalletEntryCapReached);
+        }
+        require!(allowance == 0 || tickets <= allowance, GivefiError::AllowanceExceeded);
+
+        self.giveaway_id = giveaway.id;
+        self.user = user;
+        self.tickets = tickets;
+        self.entries = math::add(self.entries, 1)?;
+        self.bump = bump;
+        Ok(())
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct GiveawayEntry {
     pub giveaway_id: u64,
     pub user: Pubkey,
-    pub entry_number: u64,
+    pub payer: Pubkey,
+    // Tickets `first_entry_number..first_entry_number + count` belong to this entry
+    pub first_entry_number: u64,
+    pub count: u64,
+    // Free tickets earned through the entry gate, included in `count` but never refunded
+    pub bonus_tickets: u64,
+    // What the vault received for this entry, which is what a refund returns
+    pub amount_paid: u64,
     pub timestamp: i64,
     pub payment_type: PaymentType,
     pub claimed: bool,
@@ -866,4 +4416,160 @@ pub enum GivefiError {
     FallbackTooEarly,
     #[msg("Invalid owner - must be giveaway authority")]
     InvalidOwner,
+    #[msg("Randomness commitment must not be empty")]
+    InvalidCommitment,
+    #[msg("Randomness already requested")]
+    RandomnessAlreadyRequested,
+    #[msg("Revealed seed does not match the commitment")]
+    InvalidReveal,
+    #[msg("Reveal window has closed, use the fallback draw")]
+    RevealWindowClosed,
+    #[msg("Unauthorized - must be program authority")]
+    Unauthorized,
+    #[msg("No oracle configured for this randomness request")]
+    OracleNotConfigured,
+    #[msg("Fulfilment not signed by the configured oracle")]
+    InvalidOracle,
+    #[msg("Randomness already fulfilled")]
+    RandomnessAlreadyFulfilled,
+    #[msg("Randomness is being provided by the oracle")]
+    OracleRandomnessPending,
+    #[msg("Too many prize tiers")]
+    TooManyPrizeTiers,
+    #[msg("Prize tier must have at least one winner")]
+    InvalidPrizeTier,
+    #[msg("Too many winners for this giveaway")]
+    TooManyWinners,
+    #[msg("Prize tier shares must sum to 10000 basis points")]
+    InvalidPrizeShares,
+    #[msg("Jackpot split must sum to 10000 basis points")]
+    InvalidJackpotSplit,
+    #[msg("Treasury fee exceeds the platform maximum")]
+    TreasuryFeeTooHigh,
+    #[msg("Winner share is below the platform minimum")]
+    WinnerShareTooLow,
+    #[msg("Prize amount too small to give every winner a share")]
+    InvalidPrizeAmount,
+    #[msg("Prize has not been escrowed yet")]
+    PrizeNotFunded,
+    #[msg("Prize already escrowed")]
+    PrizeAlreadyFunded,
+    #[msg("Prize mint does not match the giveaway")]
+    InvalidPrizeMint,
+    #[msg("Prize escrow accounts are required for this giveaway")]
+    PrizeAccountsMissing,
+    #[msg("Too many DAO members")]
+    TooManyDaoMembers,
+    #[msg("Quorum must be between 1 and the number of DAO members")]
+    InvalidQuorum,
+    #[msg("Voting period must be positive")]
+    InvalidVotingPeriod,
+    #[msg("Giveaway is awaiting DAO approval")]
+    AwaitingDaoApproval,
+    #[msg("Giveaway does not need DAO approval")]
+    ProposalNotNeeded,
+    #[msg("Signer is not a DAO member")]
+    NotDaoMember,
+    #[msg("Proposal is no longer open for voting")]
+    ProposalNotVoting,
+    #[msg("Voting period has ended")]
+    VotingClosed,
+    #[msg("Member has already voted on this proposal")]
+    AlreadyVoted,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Invalid treasury wallet")]
+    InvalidTreasuryWallet,
+    #[msg("This operation is paused")]
+    ProgramPaused,
+    #[msg("Giveaway is frozen")]
+    GiveawayFrozen,
+    #[msg("Only the creator or platform authority can cancel")]
+    UnauthorizedCancel,
+    #[msg("Refund batch is missing an owner or token account")]
+    MissingRefundAccount,
+    #[msg("Refund batch account does not belong to this giveaway")]
+    InvalidRefundEntry,
+    #[msg("Rent must be returned to the account that paid for the entry")]
+    InvalidRentPayer,
+    #[msg("Winning entries must be closed by claiming the prize")]
+    EntryIsWinner,
+    #[msg("Ticket quantity must be positive and affordable")]
+    InvalidTicketQuantity,
+    #[msg("GIVE token accounts are required to pay in GIVE")]
+    GiveAccountsMissing,
+    #[msg("Token account mint does not match the giveaway vault")]
+    InvalidTokenMint,
+    #[msg("Per-wallet entry cap must be between 1 and max entries")]
+    InvalidWalletEntryCap,
+    #[msg("This wallet already holds the maximum number of tickets")]
+    WalletEntryCapReached,
+    #[msg("This giveaway is allowlisted, a membership proof is required")]
+    AllowlistProofRequired,
+    #[msg("Signer is not on the giveaway allowlist")]
+    NotOnAllowlist,
+    #[msg("Allowlist ticket allowance exceeded")]
+    AllowanceExceeded,
+    #[msg("Entry gate accounts are missing or incomplete")]
+    GateAccountsMissing,
+    #[msg("Signer does not meet the giveaway's holder requirement")]
+    EntryGateNotMet,
+    #[msg("Bonus claim account does not match the NFT")]
+    InvalidBonusClaim,
+    #[msg("This NFT already earned bonus tickets in this giveaway")]
+    NftBonusAlreadyClaimed,
+    #[msg("Too many accepted entry mints")]
+    TooManyAcceptedMints,
+    #[msg("Mint is already accepted for this giveaway")]
+    MintAlreadyAccepted,
+    #[msg("Mint is not accepted for this giveaway")]
+    MintNotAccepted,
+    #[msg("Entry mints can only be added before the first entry")]
+    EntriesAlreadyOpen,
+    #[msg("Mint vault does not belong to this giveaway")]
+    InvalidMintVault,
+    #[msg("Accounts for an accepted mint are missing")]
+    MintAccountsMissing,
+    #[msg("Token account is not owned by the expected wallet")]
+    InvalidTokenOwner,
+    #[msg("Only giveaways that accept GIVE take a GIVE vault")]
+    GiveVaultNotNeeded,
+    #[msg("Giveaway cannot move to that status from its current one")]
+    InvalidStatusTransition,
+    #[msg("Giveaway ids from the top half of the range are allocated by the program")]
+    ReservedGiveawayId,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Vault holds less than its ledger accounts for")]
+    VaultInvariantViolated,
+    #[msg("Giveaway is not settled, failed or cancelled")]
+    GiveawayNotSettled,
+    #[msg("Vault has no surplus to sweep")]
+    NothingToReconcile,
+    #[msg("Claim window must be positive and a charity policy needs a wallet")]
+    InvalidClaimDeadline,
+    #[msg("The claim window for this giveaway has closed")]
+    ClaimWindowClosed,
+    #[msg("Winners can still claim")]
+    ClaimWindowOpen,
+    #[msg("Giveaway has no claim deadline")]
+    NoClaimDeadline,
+    #[msg("Recipient does not match the giveaway's unclaimed policy")]
+    InvalidUnclaimedRecipient,
+    #[msg("The pinned draw slot has not passed yet")]
+    SlotHashNotReady,
+    #[msg("Token program does not own the mint")]
+    InvalidTokenProgram,
+    #[msg("Losing entries stay open until every redraw is settled")]
+    RedrawPending,
+    #[msg("The pinned draw slot's hash is already recorded")]
+    DrawSlotAlreadyRecorded,
+    #[msg("Proposal is still open for voting")]
+    ProposalStillVoting,
+    #[msg("Mints with a transfer hook are not supported")]
+    TransferHookNotSupported,
+    #[msg("A forfeited winner is waiting to be redrawn")]
+    RedrawInProgress,
+    #[msg("No redraw is waiting")]
+    NoRedrawPending,
 }
\ No newline at end of file
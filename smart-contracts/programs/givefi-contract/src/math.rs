@@ -0,0 +1,31 @@
+//! Overflow-checked arithmetic for entry counts, pools and payouts.
+//!
+//! Products and ratios are taken in `u128` so intermediate values can't wrap, then narrowed back
+//! to `u64`. Anything that doesn't fit fails the instruction with `MathOverflow` instead of
+//! wrapping (release builds) or panicking (debug builds).
+
+use anchor_lang::prelude::*;
+
+use crate::GivefiError;
+
+pub fn add(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b).ok_or_else(|| error!(GivefiError::MathOverflow))
+}
+
+pub fn sub(a: u64, b: u64) -> Result<u64> {
+    a.checked_sub(b).ok_or_else(|| error!(GivefiError::MathOverflow))
+}
+
+pub fn mul(a: u64, b: u64) -> Result<u64> {
+    narrow(a as u128 * b as u128)
+}
+
+// `value * numerator / denominator`, rounded down
+pub fn mul_div(value: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    let product = value as u128 * numerator as u128;
+    narrow(product.checked_div(denominator as u128).ok_or_else(|| error!(GivefiError::MathOverflow))?)
+}
+
+pub fn narrow(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| error!(GivefiError::MathOverflow))
+}
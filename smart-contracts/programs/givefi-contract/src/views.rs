@@ -0,0 +1,202 @@
+//! Read-only views over a giveaway.
+//!
+//! The view instructions return these structs as Borsh-encoded return data, so other programs
+//! (via CPI) and clients (via simulation) can read computed figures without knowing the raw
+//! `Giveaway` layout. Every struct stays well under the 1024-byte return-data limit.
+
+use anchor_lang::prelude::*;
+
+use crate::{math, tier_slice, Giveaway, GiveawayStatus, BPS_DENOMINATOR};
+
+// Fixed-point scale for the no-win probability in `Giveaway::wallet_odds`
+const ODDS_SCALE: u128 = 1_000_000_000_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Currency {
+    Sol,
+    Give,
+    Mint(Pubkey),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct GiveawaySummary {
+    pub giveaway_id: u64,
+    pub authority: Pubkey,
+    pub status: GiveawayStatus,
+    pub frozen: bool,
+    pub current_entries: u64,
+    pub max_entries: u64,
+    pub min_participants: u64,
+    pub end_timestamp: i64,
+    // 0 once the giveaway no longer takes entries
+    pub seconds_remaining: i64,
+    pub total_winners: u64,
+    pub winners_drawn: u64,
+    pub prizes_claimed: u8,
+    pub jackpots_claimed: u8,
+    pub prize_mint: Option<Pubkey>,
+    pub prize_amount: u64,
+    pub prize_funded: bool,
+    pub jackpot_option_enabled: bool,
+    // When the last unclaimed win becomes reclaimable; None before the draw or without a deadline
+    pub claim_deadline: Option<i64>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct CurrencyPool {
+    pub currency: Currency,
+    pub price: u64,
+    pub tickets_sold: u64,
+    // Net of any transfer fee withheld by the mint
+    pub collected: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct WalletOdds {
+    pub wallet: Pubkey,
+    pub tickets: u64,
+    pub total_entries: u64,
+    pub total_winners: u64,
+    // Chance of holding at least one winning ticket if the draw happened now, rounded down
+    pub win_chance_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct CurrencyPayout {
+    pub currency: Currency,
+    pub pool: u64,
+    // Totals over every winning ticket, as claim_jackpot will pay them
+    pub winners: u64,
+    pub creator: u64,
+    pub treasury: u64,
+    // What one winner of each prize tier receives, in `prize_tiers` order
+    pub per_winner_by_tier: Vec<u64>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct PayoutBreakdown {
+    // Escrowed prize paid to one winner of each tier, in `prize_tiers` order
+    pub prize_per_winner_by_tier: Vec<u64>,
+    pub winner_counts_by_tier: Vec<u8>,
+    pub commitment_slashed: bool,
+    // Empty unless the jackpot option is enabled
+    pub jackpot: Vec<CurrencyPayout>,
+}
+
+impl Giveaway {
+    pub fn summary(&self, now: i64) -> GiveawaySummary {
+        let taking_entries = matches!(self.status, GiveawayStatus::PendingApproval | GiveawayStatus::Open);
+        GiveawaySummary {
+            giveaway_id: self.id,
+            authority: self.authority,
+            status: self.status,
+            frozen: self.frozen,
+            current_entries: self.current_entries,
+            max_entries: self.max_entries,
+            min_participants: self.min_participants,
+            end_timestamp: self.end_timestamp,
+            seconds_remaining: if taking_entries { self.end_timestamp.saturating_sub(now).max(0) } else { 0 },
+            total_winners: self.total_winners() as u64,
+            winners_drawn: self.winners.len() as u64,
+            prizes_claimed: self.prizes_claimed,
+            jackpots_claimed: self.jackpots_claimed,
+            prize_mint: self.prize_mint,
+            prize_amount: self.prize_amount,
+            prize_funded: self.prize_funded,
+            jackpot_option_enabled: self.jackpot_option_enabled,
+            claim_deadline: self
+                .claim_deadline
+                .and_then(|_| self.winner_deadlines.iter().max().copied()),
+        }
+    }
+
+    // SOL first, then GIVE if it is accepted, then every accepted mint in order
+    pub fn pools(&self) -> Result<Vec<CurrencyPool>> {
+        let mut pools = vec![CurrencyPool {
+            currency: Currency::Sol,
+            price: self.entry_cost_sol,
+            tickets_sold: self.sol_entries,
+            collected: self.sol_pool()?,
+        }];
+        if let Some(price) = self.entry_cost_give {
+            pools.push(CurrencyPool {
+                currency: Currency::Give,
+                price,
+                tickets_sold: self.give_entries,
+                collected: self.give_pool,
+            });
+        }
+        pools.extend(self.accepted_mints.iter().map(|accepted| CurrencyPool {
+            currency: Currency::Mint(accepted.mint),
+            price: accepted.price,
+            tickets_sold: accepted.entries,
+            collected: accepted.pool,
+        }));
+        Ok(pools)
+    }
+
+    // Winners are drawn without replacement, so the chance of winning nothing is
+    // prod_{i < w} (n - t - i) / (n - i) for t tickets out of n with w winners
+    pub fn wallet_odds(&self, wallet: Pubkey, tickets: u64) -> WalletOdds {
+        let total_entries = self.current_entries;
+        let total_winners = self.total_winners() as u64;
+        let tickets = tickets.min(total_entries);
+        let draws = total_winners.min(total_entries);
+
+        let win_chance_bps = if tickets == 0 || draws == 0 {
+            0
+        } else if total_entries - tickets < draws {
+            BPS_DENOMINATOR as u16
+        } else {
+            let lose = (0..draws).fold(ODDS_SCALE, |lose, i| {
+                lose * (total_entries - tickets - i) as u128 / (total_entries - i) as u128
+            });
+            ((ODDS_SCALE - lose) * BPS_DENOMINATOR as u128 / ODDS_SCALE) as u16
+        };
+
+        WalletOdds { wallet, tickets, total_entries, total_winners, win_chance_bps }
+    }
+
+    pub fn payout_breakdown(&self) -> Result<PayoutBreakdown> {
+        let jackpot = if self.jackpot_option_enabled {
+            self.pools()?
+                .into_iter()
+                .map(|pool| self.currency_payout(pool.currency, pool.collected))
+                .collect::<Result<_>>()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(PayoutBreakdown {
+            prize_per_winner_by_tier: self
+                .prize_tiers
+                .iter()
+                .map(|tier| tier_slice(self.prize_amount, tier))
+                .collect::<Result<_>>()?,
+            winner_counts_by_tier: self.prize_tiers.iter().map(|tier| tier.count).collect(),
+            commitment_slashed: self.commitment_slashed,
+            jackpot,
+        })
+    }
+
+    // Mirrors claim_jackpot: each winning ticket releases its tier's slice, which is then split
+    fn currency_payout(&self, currency: Currency, pool: u64) -> Result<CurrencyPayout> {
+        let mut payout = CurrencyPayout {
+            currency,
+            pool,
+            winners: 0,
+            creator: 0,
+            treasury: 0,
+            per_winner_by_tier: Vec::with_capacity(self.prize_tiers.len()),
+        };
+        for (index, tier) in self.prize_tiers.iter().enumerate() {
+            let shares = self.claim_shares(pool, &[index])?;
+            let count = tier.count as u64;
+            payout.winners = math::add(payout.winners, math::mul(shares.winner, count)?)?;
+            payout.creator = math::add(payout.creator, math::mul(shares.creator, count)?)?;
+            payout.treasury = math::add(payout.treasury, math::mul(shares.treasury, count)?)?;
+            payout.per_winner_by_tier.push(shares.winner);
+        }
+        Ok(payout)
+    }
+}
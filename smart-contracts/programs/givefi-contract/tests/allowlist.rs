@@ -0,0 +1,62 @@
+use anchor_lang::prelude::Pubkey;
+use givefi_contract::allowlist::{self, AllowlistError, AllowlistTree};
+
+fn members_csv(count: usize) -> (Vec<Pubkey>, String) {
+    let users: Vec<Pubkey> = (0..count).map(|_| Pubkey::new_unique()).collect();
+    let mut csv = String::from("pubkey,allowance\n");
+    for (index, user) in users.iter().enumerate() {
+        // Every other member gets an explicit allowance
+        if index % 2 == 0 {
+            csv.push_str(&format!("{}\n", user));
+        } else {
+            csv.push_str(&format!("{},{}\n", user, index));
+        }
+    }
+    (users, csv)
+}
+
+#[test]
+fn every_member_proves_membership() {
+    for count in [1, 2, 3, 7, 8, 33] {
+        let (users, csv) = members_csv(count);
+        let tree = AllowlistTree::from_csv(&csv).unwrap();
+        let root = tree.root();
+
+        for (index, user) in users.iter().enumerate() {
+            let (allowance, proof) = tree.proof_for(user).unwrap();
+            assert_eq!(allowance, if index % 2 == 0 { 0 } else { index as u64 });
+            assert!(allowlist::verify(&root, allowlist::leaf(user, allowance), &proof));
+        }
+    }
+}
+
+#[test]
+fn rejects_outsiders_and_forged_allowances() {
+    let (users, csv) = members_csv(5);
+    let tree = AllowlistTree::from_csv(&csv).unwrap();
+    let root = tree.root();
+
+    let (allowance, proof) = tree.proof_for(&users[1]).unwrap();
+    assert!(!allowlist::verify(&root, allowlist::leaf(&users[1], allowance + 1), &proof));
+    assert!(!allowlist::verify(&root, allowlist::leaf(&Pubkey::new_unique(), allowance), &proof));
+    assert!(tree.proof_for(&Pubkey::new_unique()).is_none());
+}
+
+#[test]
+fn reports_bad_csv_rows() {
+    let user = Pubkey::new_unique();
+
+    assert_eq!(AllowlistTree::from_csv("pubkey\n").err(), Some(AllowlistError::Empty));
+    assert_eq!(
+        AllowlistTree::from_csv("not-a-key\n").err(),
+        Some(AllowlistError::InvalidPubkey { line: 1, value: "not-a-key".to_string() })
+    );
+    assert_eq!(
+        AllowlistTree::from_csv(&format!("# members\n{},many\n", user)).err(),
+        Some(AllowlistError::InvalidAllowance { line: 2, value: "many".to_string() })
+    );
+    assert_eq!(
+        AllowlistTree::from_csv(&format!("{}\n{},3\n", user, user)).err(),
+        Some(AllowlistError::DuplicateMember(user))
+    );
+}
@@ -0,0 +1,300 @@
+mod common;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_spl::{token, token_2022};
+use common::*;
+use givefi_contract::{accounts, instruction, GivefiError, Giveaway, GiveawayStatus};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+const PRIZE_AMOUNT: u64 = 1_000_000;
+
+struct Prize {
+    mint: Pubkey,
+    creator_account: Pubkey,
+    token_program: Pubkey,
+}
+
+fn prize_vault_address(id: u64) -> Pubkey {
+    pda(&[b"prize_vault", &id.to_le_bytes()])
+}
+
+fn cancel_ix(id: u64, creator: &Keypair, canceller: &Keypair, prize: Option<&Prize>) -> Instruction {
+    givefi_ix(
+        accounts::CancelGiveaway {
+            giveaway: giveaway_address(id),
+            program_state: program_state(),
+            canceller: canceller.pubkey(),
+            creator: creator.pubkey(),
+            prize_vault: prize.map(|_| prize_vault_address(id)),
+            creator_prize_account: prize.map(|prize| prize.creator_account),
+            prize_mint: prize.map(|prize| prize.mint),
+            token_program: prize.map(|prize| prize.token_program),
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CancelGiveaway {},
+    )
+}
+
+fn dao_cancel_ixs(id: u64, member: &Keypair) -> [Instruction; 2] {
+    let proposal = pda(&[b"cancel_proposal", &id.to_le_bytes()]);
+    let dao_config = pda(&[b"dao_config"]);
+    let propose = givefi_ix(
+        accounts::ProposeCancellation {
+            giveaway: giveaway_address(id),
+            proposal,
+            dao_config,
+            member: member.pubkey(),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::ProposeCancellation {},
+    );
+    let vote = givefi_ix(
+        accounts::CastVote {
+            giveaway: giveaway_address(id),
+            proposal,
+            dao_config,
+            member: member.pubkey(),
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CastVote { approve: true },
+    );
+    [propose, vote]
+}
+
+fn crank_ix(id: u64, entrant: &Keypair, first_entry_number: u64, cranker: &Keypair) -> Instruction {
+    let mut ix = givefi_ix(
+        accounts::CrankRefunds {
+            giveaway: giveaway_address(id),
+            giveaway_vault: vault_address(id),
+            giveaway_token_vault: None,
+            give_mint: None,
+            program_state: program_state(),
+            cranker: cranker.pubkey(),
+            token_program: None,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CrankRefunds {},
+    );
+    // [entry, owner, rent payer, participant]; the entrant paid its own rent
+    ix.accounts.push(AccountMeta::new(entry_address(id, first_entry_number), false));
+    ix.accounts.push(AccountMeta::new(entrant.pubkey(), false));
+    ix.accounts.push(AccountMeta::new(entrant.pubkey(), false));
+    ix.accounts.push(AccountMeta::new(participant_address(id, &entrant.pubkey()), false));
+    ix
+}
+
+// Creates giveaway 1 with `PRIZE_AMOUNT` of a fresh mint escrowed
+async fn escrowed_giveaway(
+    ctx: &mut ProgramTestContext,
+    creator: &Keypair,
+    token_program: Pubkey,
+    extension: Option<MintExtension>,
+) -> Prize {
+    let mint = create_mint(ctx, token_program, extension).await;
+    let creator_account = token_account(ctx, token_program, mint, creator.pubkey(), PRIZE_AMOUNT).await;
+    let now = clock(ctx).await.unix_timestamp;
+    let mut args = create_args(1, now, 1, 2);
+    args.prize_mint = Some(mint);
+    args.prize_amount = PRIZE_AMOUNT;
+    create_giveaway(ctx, creator, args).await;
+
+    let fund = givefi_ix(
+        accounts::FundPrize {
+            giveaway: giveaway_address(1),
+            prize_vault: prize_vault_address(1),
+            prize_mint: mint,
+            authority_prize_account: creator_account,
+            program_state: program_state(),
+            authority: creator.pubkey(),
+            token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::FundPrize {},
+    );
+    send(ctx, &[fund], &[creator]).await.unwrap();
+    Prize { mint, creator_account, token_program }
+}
+
+#[tokio::test]
+async fn only_creator_or_platform_can_cancel() {
+    let creator = Keypair::new();
+    let entrant = Keypair::new();
+    let mut ctx = program_test(&[&creator, &entrant]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    create_giveaway(&mut ctx, &creator, create_args(1, now, 1, 2)).await;
+    enter_sol(&mut ctx, 1, 0, &entrant).await;
+
+    let result = send(&mut ctx, &[cancel_ix(1, &creator, &entrant, None)], &[&entrant]).await;
+    assert_givefi_error(result, GivefiError::UnauthorizedCancel);
+
+    let platform = ctx.payer.insecure_clone();
+    send(&mut ctx, &[cancel_ix(1, &creator, &platform, None)], &[]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Cancelled);
+}
+
+// The DAO cancels through a proposal: no single member can, but a quorum of votes does, and the
+// escrowed prize then goes back to the creator through reclaim_prize
+#[tokio::test]
+async fn dao_quorum_can_cancel() {
+    let creator = Keypair::new();
+    let entrant = Keypair::new();
+    let members = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let mut ctx = program_test(&[&creator, &entrant, &members[0], &members[1], &members[2]]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let configure = givefi_ix(
+        accounts::ConfigureDao {
+            program_state: program_state(),
+            dao_config: pda(&[b"dao_config"]),
+            authority: ctx.payer.pubkey(),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::ConfigureDao {
+            members: members.iter().map(Keypair::pubkey).collect(),
+            quorum: 2,
+            voting_period_secs: 3_600,
+            approval_required: false,
+        },
+    );
+    send(&mut ctx, &[configure], &[]).await.unwrap();
+    let prize = escrowed_giveaway(&mut ctx, &creator, token::ID, None).await;
+    enter_sol(&mut ctx, 1, 0, &entrant).await;
+
+    let result = send(&mut ctx, &[cancel_ix(1, &creator, &members[0], None)], &[&members[0]]).await;
+    assert_givefi_error(result, GivefiError::UnauthorizedCancel);
+    let [propose, _] = dao_cancel_ixs(1, &entrant);
+    assert_givefi_error(send(&mut ctx, &[propose], &[&entrant]).await, GivefiError::NotDaoMember);
+
+    send(&mut ctx, &dao_cancel_ixs(1, &members[0]), &[&members[0]]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Open);
+    let proposal = pda(&[b"cancel_proposal", &1u64.to_le_bytes()]);
+    let close_proposal = givefi_ix(
+        accounts::CloseProposal {
+            giveaway: giveaway_address(1),
+            proposal,
+            proposer: members[0].pubkey(),
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CloseProposal {},
+    );
+    let result = send(&mut ctx, std::slice::from_ref(&close_proposal), &[]).await;
+    assert_givefi_error(result, GivefiError::ProposalStillVoting);
+
+    let [_, vote] = dao_cancel_ixs(1, &members[1]);
+    send(&mut ctx, &[vote], &[&members[1]]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Cancelled);
+
+    let rent = lamports(&mut ctx, proposal).await;
+    let proposer_before = lamports(&mut ctx, members[0].pubkey()).await;
+    send(&mut ctx, &[close_proposal], &[]).await.unwrap();
+    assert!(!account_exists(&mut ctx, proposal).await);
+    assert_eq!(lamports(&mut ctx, members[0].pubkey()).await - proposer_before, rent);
+
+    let reclaim = givefi_ix(
+        accounts::ReclaimPrize {
+            giveaway: giveaway_address(1),
+            prize_vault: prize_vault_address(1),
+            authority_prize_account: prize.creator_account,
+            prize_mint: prize.mint,
+            authority: creator.pubkey(),
+            token_program: prize.token_program,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::ReclaimPrize {},
+    );
+    send(&mut ctx, &[reclaim], &[&creator]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, prize.creator_account).await, PRIZE_AMOUNT);
+}
+
+// The crank bounty is a platform setting: off by default, and once set it comes out of the
+// closed entry's rent before the rest goes back to the payer, along with the rent of the
+// wallet's Participant account once its last entry is closed
+#[tokio::test]
+async fn crank_bounty_is_paid_only_when_configured() {
+    let creator = Keypair::new();
+    let cranker = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new()];
+    let mut ctx = program_test(&[&creator, &cranker, &entrants[0], &entrants[1]]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    create_giveaway(&mut ctx, &creator, create_args(1, now, 1, 2)).await;
+    enter_sol(&mut ctx, 1, 0, &entrants[0]).await;
+    enter_sol(&mut ctx, 1, 1, &entrants[1]).await;
+    send(&mut ctx, &[cancel_ix(1, &creator, &creator, None)], &[&creator]).await.unwrap();
+
+    for (ticket, bounty) in [(0, 0), (1, 5_000)] {
+        let entrant = &entrants[ticket as usize];
+        let set_bounty = givefi_ix(
+            accounts::UpdateProgramState {
+                program_state: program_state(),
+                authority: ctx.payer.pubkey(),
+                event_authority: event_authority(),
+                program: givefi_contract::ID,
+            },
+            instruction::SetCrankBounty { crank_bounty_lamports: bounty },
+        );
+        let entry_rent = lamports(&mut ctx, entry_address(1, ticket)).await;
+        let participant_rent = lamports(&mut ctx, participant_address(1, &entrant.pubkey())).await;
+        let entrant_before = lamports(&mut ctx, entrant.pubkey()).await;
+        let cranker_before = lamports(&mut ctx, cranker.pubkey()).await;
+        send(&mut ctx, &[set_bounty, crank_ix(1, entrant, ticket, &cranker)], &[&cranker]).await.unwrap();
+
+        assert_eq!(lamports(&mut ctx, cranker.pubkey()).await - cranker_before, bounty);
+        let returned = ENTRY_COST + entry_rent + participant_rent - bounty;
+        assert_eq!(lamports(&mut ctx, entrant.pubkey()).await - entrant_before, returned);
+        assert!(!account_exists(&mut ctx, entry_address(1, ticket)).await);
+        assert!(!account_exists(&mut ctx, participant_address(1, &entrant.pubkey())).await);
+    }
+}
+
+#[tokio::test]
+async fn cancel_returns_classic_and_token_2022_prizes() {
+    for token_program in [token::ID, token_2022::ID] {
+        let creator = Keypair::new();
+        let mut ctx = program_test(&[&creator]).start_with_context().await;
+        initialize(&mut ctx, Keypair::new().pubkey()).await;
+        let prize = escrowed_giveaway(&mut ctx, &creator, token_program, None).await;
+
+        send(&mut ctx, &[cancel_ix(1, &creator, &creator, Some(&prize))], &[&creator]).await.unwrap();
+        assert_eq!(token_balance(&mut ctx, prize.creator_account).await, PRIZE_AMOUNT);
+        assert!(!account_exists(&mut ctx, prize_vault_address(1)).await);
+    }
+}
+
+#[tokio::test]
+async fn cancel_harvests_withheld_fees_before_closing_the_escrow() {
+    let creator = Keypair::new();
+    let mut ctx = program_test(&[&creator]).start_with_context().await;
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    // 1% fee on the way in and again on the way out
+    let prize = escrowed_giveaway(&mut ctx, &creator, token_2022::ID, Some(MintExtension::TransferFee(100, u64::MAX))).await;
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.prize_amount, PRIZE_AMOUNT - PRIZE_AMOUNT / 100);
+
+    send(&mut ctx, &[cancel_ix(1, &creator, &creator, Some(&prize))], &[&creator]).await.unwrap();
+    let returned = giveaway.prize_amount - giveaway.prize_amount / 100;
+    assert_eq!(token_balance(&mut ctx, prize.creator_account).await, returned);
+    assert!(!account_exists(&mut ctx, prize_vault_address(1)).await);
+}
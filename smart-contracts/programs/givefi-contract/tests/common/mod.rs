@@ -0,0 +1,468 @@
+// Shared setup for the solana-program-test suites. Both programs run natively in the test
+// process, so these need no validator and no SBF build.
+#![allow(dead_code)]
+
+use anchor_lang::prelude::{AccountInfo, Clock, Pubkey};
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, spl_associated_token_account};
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    interest_bearing_mint, transfer_fee, transfer_hook, ExtensionType, StateWithExtensions,
+};
+use givefi_contract::{
+    accounts, instruction, Giveaway, GiveawayStatus, JackpotSplit, PrizeTier, VaultLedger, BPS_DENOMINATOR,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::hash::Hash;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::slot_hashes::SlotHashes;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+
+pub const ENTRY_COST: u64 = LAMPORTS_PER_SOL / 10;
+pub const SEED_PREIMAGE: [u8; 32] = [7; 32];
+pub const MINT_DECIMALS: u8 = 6;
+
+// Anchor entrypoints tie the account slice to the accounts' own lifetime, which the
+// program-test processor signature can't express; leaking a copy per instruction satisfies it.
+fn givefi_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    givefi_contract::entry(program_id, accounts, data)
+}
+
+fn oracle_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    mock_oracle::entry(program_id, accounts, data)
+}
+
+pub fn program_test(wallets: &[&Keypair]) -> ProgramTest {
+    let mut test = ProgramTest::new("givefi_contract", givefi_contract::ID, processor!(givefi_entry));
+    test.add_program("mock_oracle", mock_oracle::ID, processor!(oracle_entry));
+    for wallet in wallets {
+        test.add_account(
+            wallet.pubkey(),
+            Account { lamports: 10 * LAMPORTS_PER_SOL, owner: system_program::ID, ..Account::default() },
+        );
+    }
+    test
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &givefi_contract::ID).0
+}
+
+pub fn event_authority() -> Pubkey {
+    pda(&[b"__event_authority"])
+}
+
+pub fn program_state() -> Pubkey {
+    pda(&[b"program_state"])
+}
+
+pub fn giveaway_address(id: u64) -> Pubkey {
+    pda(&[b"giveaway", &id.to_le_bytes()])
+}
+
+pub fn vault_address(id: u64) -> Pubkey {
+    pda(&[b"giveaway_vault", &id.to_le_bytes()])
+}
+
+pub fn entry_address(id: u64, first_entry_number: u64) -> Pubkey {
+    pda(&[b"entry", &id.to_le_bytes(), &first_entry_number.to_le_bytes()])
+}
+
+pub fn participant_address(id: u64, user: &Pubkey) -> Pubkey {
+    pda(&[b"participant", &id.to_le_bytes(), user.as_ref()])
+}
+
+pub fn randomness_request_address(id: u64) -> Pubkey {
+    pda(&[b"randomness_request", &id.to_le_bytes()])
+}
+
+pub fn givefi_ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction { program_id: givefi_contract::ID, accounts: accounts.to_account_metas(None), data: data.data() }
+}
+
+pub async fn send(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await?;
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction =
+        Transaction::new_signed_with_payer(instructions, Some(&ctx.payer.pubkey()), &all_signers, blockhash);
+    ctx.banks_client.process_transaction(transaction).await
+}
+
+pub async fn fetch<T: AccountDeserialize>(ctx: &mut ProgramTestContext, address: Pubkey) -> T {
+    let account = ctx.banks_client.get_account(address).await.unwrap().expect("account exists");
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn clock(ctx: &mut ProgramTestContext) -> Clock {
+    ctx.banks_client.get_sysvar::<Clock>().await.unwrap()
+}
+
+// Moves the bank forward `slots` slots and, when given, the clock to `unix_timestamp`
+pub async fn advance(ctx: &mut ProgramTestContext, slots: u64, unix_timestamp: Option<i64>) {
+    let slot = clock(ctx).await.slot + slots;
+    ctx.warp_to_slot(slot).unwrap();
+    if let Some(unix_timestamp) = unix_timestamp {
+        let mut clock = clock(ctx).await;
+        clock.unix_timestamp = unix_timestamp;
+        ctx.set_sysvar(&clock);
+    }
+}
+
+// Warps past `slot` and one slot more, so the first bank at or after it has a SlotHashes entry
+pub async fn pass_slot(ctx: &mut ProgramTestContext, slot: u64) {
+    let current = clock(ctx).await.slot;
+    advance(ctx, slot.saturating_sub(current) + 1, None).await;
+    advance(ctx, 1, None).await;
+}
+
+// Leaves only entries newer than every pinned slot in SlotHashes, as if ~512 slots had passed
+pub async fn age_out_slot_hashes(ctx: &mut ProgramTestContext) {
+    let slot = clock(ctx).await.slot;
+    ctx.set_sysvar(&SlotHashes::new(&[(slot, Hash::new_unique())]));
+    advance(ctx, 1, None).await;
+}
+
+// Custom program errors surface as `Custom(6000 + variant index)`
+pub fn assert_givefi_error(result: Result<(), BanksClientError>, error: givefi_contract::GivefiError) {
+    let message = format!("{:?}", result.expect_err("instruction should fail"));
+    let code = anchor_lang::error::ERROR_CODE_OFFSET + error as u32;
+    assert!(message.contains(&format!("Custom({code})")), "expected {error:?} ({code}), got {message}");
+}
+
+pub async fn initialize(ctx: &mut ProgramTestContext, treasury: Pubkey) {
+    let ix = givefi_ix(
+        accounts::InitializeProgram {
+            program_state: program_state(),
+            authority: ctx.payer.pubkey(),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::InitializeProgram { treasury_wallet: treasury },
+    );
+    send(ctx, &[ix], &[]).await.unwrap();
+}
+
+pub async fn set_oracle_program(ctx: &mut ProgramTestContext, oracle_program: Pubkey) {
+    let ix = givefi_ix(
+        accounts::UpdateProgramState {
+            program_state: program_state(),
+            authority: ctx.payer.pubkey(),
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::SetOracleProgram { oracle_program },
+    );
+    send(ctx, &[ix], &[]).await.unwrap();
+}
+
+// A SOL-only giveaway ending in an hour with early end enabled; callers adjust the fields
+pub fn create_args(id: u64, now: i64, winners: u8, min_participants: u64) -> instruction::CreateGiveaway {
+    instruction::CreateGiveaway {
+        giveaway_id: Some(id),
+        entry_cost_sol: ENTRY_COST,
+        entry_cost_give: None,
+        max_entries: 100,
+        min_participants,
+        max_entries_per_wallet: None,
+        allowlist_root: None,
+        entry_gate: None,
+        prize_description: "Program test prize".to_string(),
+        end_timestamp: now + 3600,
+        jackpot_option_enabled: true,
+        early_end_enabled: true,
+        randomness_commitment: hash(&SEED_PREIMAGE).to_bytes(),
+        prize_tiers: vec![PrizeTier { count: winners, share_bps: BPS_DENOMINATOR as u16, description: String::new() }],
+        jackpot_split: None,
+        prize_mint: None,
+        prize_amount: 0,
+        claim_deadline: None,
+    }
+}
+
+pub async fn create_giveaway(ctx: &mut ProgramTestContext, creator: &Keypair, args: instruction::CreateGiveaway) {
+    let id = args.giveaway_id.unwrap();
+    let ix = givefi_ix(
+        accounts::CreateGiveaway {
+            program_state: program_state(),
+            giveaway: giveaway_address(id),
+            giveaway_vault: vault_address(id),
+            giveaway_token_vault: None,
+            give_mint: None,
+            creator_index: pda(&[b"creator_index", creator.pubkey().as_ref()]),
+            authority: creator.pubkey(),
+            token_program: None,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        args,
+    );
+    send(ctx, &[ix], &[creator]).await.unwrap();
+}
+
+pub async fn enter_sol(ctx: &mut ProgramTestContext, id: u64, first_entry_number: u64, user: &Keypair) {
+    let ix = givefi_ix(
+        accounts::EnterGiveawaySol {
+            giveaway: giveaway_address(id),
+            entry: entry_address(id, first_entry_number),
+            participant: participant_address(id, &user.pubkey()),
+            giveaway_vault: vault_address(id),
+            program_state: program_state(),
+            gate_token_account: None,
+            gate_metadata: None,
+            user: user.pubkey(),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::EnterGiveawaySol { allowlist_proof: None },
+    );
+    send(ctx, &[ix], &[user]).await.unwrap();
+}
+
+pub async fn end_early(ctx: &mut ProgramTestContext, id: u64, creator: &Keypair) {
+    let ix = givefi_ix(
+        accounts::EndRaffleEarly {
+            giveaway: giveaway_address(id),
+            authority: creator.pubkey(),
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::EndRaffleEarly {},
+    );
+    send(ctx, &[ix], &[creator]).await.unwrap();
+}
+
+pub fn request_randomness_ix(id: u64, creator: &Keypair) -> Instruction {
+    givefi_ix(
+        accounts::RequestRandomness {
+            giveaway: giveaway_address(id),
+            randomness_request: randomness_request_address(id),
+            program_state: program_state(),
+            authority: creator.pubkey(),
+            slot_hashes: sysvar::slot_hashes::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::RequestRandomness {},
+    )
+}
+
+pub fn oracle_fulfill_ix(id: u64, caller: Pubkey, result: [u8; 32]) -> Instruction {
+    let accounts = mock_oracle::accounts::Fulfill {
+        giveaway: giveaway_address(id),
+        randomness_request: randomness_request_address(id),
+        program_state: program_state(),
+        oracle_authority: Pubkey::find_program_address(&[givefi_contract::ORACLE_AUTHORITY_SEED], &mock_oracle::ID).0,
+        caller,
+        givefi_event_authority: event_authority(),
+        givefi_program: givefi_contract::ID,
+    };
+    Instruction {
+        program_id: mock_oracle::ID,
+        accounts: accounts.to_account_metas(None),
+        data: mock_oracle::instruction::Fulfill { result }.data(),
+    }
+}
+
+// A Token-2022 mint extension, with the payer as its authority
+pub enum MintExtension {
+    // (basis points, maximum fee)
+    TransferFee(u16, u64),
+    // Points at a hook program that doesn't need to exist for the mint to be created
+    TransferHook,
+    // Rate in basis points
+    InterestBearing(i16),
+}
+
+// Creates a mint under `token_program` with the payer as mint authority. A Token-2022 mint can
+// carry one extension.
+pub async fn create_mint(ctx: &mut ProgramTestContext, token_program: Pubkey, extension: Option<MintExtension>) -> Pubkey {
+    let mint = Keypair::new();
+    let payer = ctx.payer.pubkey();
+    let extensions: &[ExtensionType] = match extension {
+        None => &[],
+        Some(MintExtension::TransferFee(..)) => &[ExtensionType::TransferFeeConfig],
+        Some(MintExtension::TransferHook) => &[ExtensionType::TransferHook],
+        Some(MintExtension::InterestBearing(_)) => &[ExtensionType::InterestBearingConfig],
+    };
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(extensions).unwrap();
+    let rent = ctx.banks_client.get_rent().await.unwrap().minimum_balance(space);
+
+    let mut instructions =
+        vec![system_instruction::create_account(&payer, &mint.pubkey(), rent, space as u64, &token_program)];
+    match extension {
+        None => {}
+        Some(MintExtension::TransferFee(basis_points, maximum_fee)) => instructions.push(
+            transfer_fee::instruction::initialize_transfer_fee_config(
+                &token_program,
+                &mint.pubkey(),
+                Some(&payer),
+                Some(&payer),
+                basis_points,
+                maximum_fee,
+            )
+            .unwrap(),
+        ),
+        Some(MintExtension::TransferHook) => instructions.push(
+            transfer_hook::instruction::initialize(&token_program, &mint.pubkey(), Some(payer), Some(Pubkey::new_unique()))
+                .unwrap(),
+        ),
+        Some(MintExtension::InterestBearing(rate)) => instructions.push(
+            interest_bearing_mint::instruction::initialize(&token_program, &mint.pubkey(), Some(payer), rate).unwrap(),
+        ),
+    }
+    instructions.push(
+        spl_token_2022::instruction::initialize_mint2(&token_program, &mint.pubkey(), &payer, None, MINT_DECIMALS)
+            .unwrap(),
+    );
+    send(ctx, &instructions, &[&mint]).await.unwrap();
+    mint.pubkey()
+}
+
+// Creates `owner`'s associated account for `mint` and mints `amount` into it
+pub async fn token_account(
+    ctx: &mut ProgramTestContext,
+    token_program: Pubkey,
+    mint: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+) -> Pubkey {
+    let payer = ctx.payer.pubkey();
+    let address = get_associated_token_address_with_program_id(&owner, &mint, &token_program);
+    let mut instructions = vec![spl_associated_token_account::instruction::create_associated_token_account(
+        &payer,
+        &owner,
+        &mint,
+        &token_program,
+    )];
+    if amount > 0 {
+        instructions.push(
+            spl_token_2022::instruction::mint_to(&token_program, &mint, &address, &payer, &[], amount).unwrap(),
+        );
+    }
+    send(ctx, &instructions, &[]).await.unwrap();
+    address
+}
+
+pub async fn token_balance(ctx: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    let account = ctx.banks_client.get_account(address).await.unwrap().expect("token account exists");
+    StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data).unwrap().base.amount
+}
+
+pub async fn account_exists(ctx: &mut ProgramTestContext, address: Pubkey) -> bool {
+    ctx.banks_client.get_account(address).await.unwrap().is_some()
+}
+
+pub async fn lamports(ctx: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    ctx.banks_client.get_balance(address).await.unwrap()
+}
+
+// Creates giveaway `args`, buys one ticket per entrant in order and draws it through the mock
+// oracle, which must already be the configured oracle program
+pub async fn drawn_giveaway(
+    ctx: &mut ProgramTestContext,
+    creator: &Keypair,
+    entrants: &[Keypair],
+    args: instruction::CreateGiveaway,
+) -> givefi_contract::Giveaway {
+    let id = args.giveaway_id.unwrap();
+    create_giveaway(ctx, creator, args).await;
+    for (ticket, entrant) in entrants.iter().enumerate() {
+        enter_sol(ctx, id, ticket as u64, entrant).await;
+    }
+    end_early(ctx, id, creator).await;
+    advance(ctx, 1, None).await;
+    let payer = ctx.payer.pubkey();
+    send(ctx, &[request_randomness_ix(id, creator), oracle_fulfill_ix(id, payer, [42; 32])], &[creator])
+        .await
+        .unwrap();
+    fetch(ctx, giveaway_address(id)).await
+}
+
+pub fn claim_prize_ix(id: u64, first_entry_number: u64, user: &Keypair) -> Instruction {
+    givefi_ix(
+        accounts::ClaimPrize {
+            giveaway: giveaway_address(id),
+            entry: entry_address(id, first_entry_number),
+            participant: participant_address(id, &user.pubkey()),
+            user: user.pubkey(),
+            payer: user.pubkey(),
+            program_state: program_state(),
+            prize_vault: None,
+            user_prize_account: None,
+            prize_mint: None,
+            token_program: None,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::ClaimPrize {},
+    )
+}
+
+// An open SOL giveaway with `entries` tickets sold at 1_000 lamports, for tests that exercise
+// the account's methods without a bank
+pub fn giveaway_fixture(entries: u64, prize_tiers: Vec<PrizeTier>) -> Giveaway {
+    Giveaway {
+        id: 7,
+        authority: Pubkey::new_unique(),
+        entry_cost_sol: 1_000,
+        entry_cost_give: None,
+        max_entries: 1_000,
+        min_participants: 1,
+        max_entries_per_wallet: None,
+        allowlist_root: None,
+        entry_gate: None,
+        current_entries: entries,
+        sol_entries: entries,
+        give_entries: 0,
+        give_pool: 0,
+        give_ledger: VaultLedger::default(),
+        accepted_mints: Vec::new(),
+        sol_ledger: VaultLedger::default(),
+        prize_description: String::new(),
+        prize_mint: None,
+        prize_amount: 0,
+        prize_funded: true,
+        dao_approved: true,
+        status: GiveawayStatus::Open,
+        frozen: false,
+        end_timestamp: 1_000,
+        jackpot_option_enabled: true,
+        jackpot_split: JackpotSplit { winner_bps: 6_000, creator_bps: 3_300, treasury_bps: 700 },
+        early_end_enabled: false,
+        prize_tiers,
+        winners: Vec::new(),
+        winner_deadlines: Vec::new(),
+        prizes_claimed: 0,
+        jackpots_claimed: 0,
+        forfeited: 0,
+        retired_tickets: Vec::new(),
+        pending_redraws: Vec::new(),
+        redraw_slot: 0,
+        claim_deadline: None,
+        drawn_at: 0,
+        randomness_commitment: [0; 32],
+        recorded_slot: 0,
+        recorded_slot_hash: [0; 32],
+        oracle_requested: false,
+        commitment_slashed: false,
+        bump: 255,
+    }
+}
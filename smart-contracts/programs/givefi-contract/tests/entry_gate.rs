@@ -0,0 +1,168 @@
+mod common;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::AnchorSerialize;
+use anchor_spl::metadata::mpl_token_metadata::accounts::Metadata;
+use anchor_spl::metadata::mpl_token_metadata::types::{Collection, Key};
+use anchor_spl::token;
+use common::*;
+use givefi_contract::{accounts, instruction, EntryGate, GivefiError, GiveawayEntry, NftBonus};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+
+const BONUS_TICKETS: u8 = 2;
+
+struct Nft {
+    token_account: Pubkey,
+    metadata: Pubkey,
+    bonus_claim: Pubkey,
+}
+
+// Mints `owner` one token of a fresh mint and fakes its Metaplex metadata as a verified member of
+// `collection`; the program only reads the metadata account, so no Metaplex program is needed
+async fn collection_nft(ctx: &mut ProgramTestContext, owner: &Keypair, collection: Pubkey) -> Nft {
+    let mint = create_mint(ctx, token::ID, None).await;
+    let token_account = token_account(ctx, token::ID, mint, owner.pubkey(), 1).await;
+    let metadata = Metadata {
+        key: Key::MetadataV1,
+        update_authority: Pubkey::new_unique(),
+        mint,
+        name: String::new(),
+        symbol: String::new(),
+        uri: String::new(),
+        seller_fee_basis_points: 0,
+        creators: None,
+        primary_sale_happened: false,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: None,
+        collection: Some(Collection { verified: true, key: collection }),
+        uses: None,
+        collection_details: None,
+        programmable_config: None,
+    };
+    let data = metadata.try_to_vec().unwrap();
+    let address = Metadata::find_pda(&mint).0;
+    let rent = ctx.banks_client.get_rent().await.unwrap().minimum_balance(data.len());
+    ctx.set_account(
+        &address,
+        &Account { lamports: rent, data, owner: anchor_spl::metadata::ID, ..Account::default() }.into(),
+    );
+    let bonus_claim = pda(&[b"nft_bonus", &1u64.to_le_bytes(), mint.as_ref()]);
+    Nft { token_account, metadata: address, bonus_claim }
+}
+
+fn gated_entry_ix(first_entry_number: u64, user: &Keypair, gate: &Nft, bonus: &Nft) -> Instruction {
+    let mut ix = givefi_ix(
+        accounts::EnterGiveawaySol {
+            giveaway: giveaway_address(1),
+            entry: entry_address(1, first_entry_number),
+            participant: participant_address(1, &user.pubkey()),
+            giveaway_vault: vault_address(1),
+            program_state: program_state(),
+            gate_token_account: Some(gate.token_account),
+            gate_metadata: Some(gate.metadata),
+            user: user.pubkey(),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::EnterGiveawaySol { allowlist_proof: None },
+    );
+    // [token account, metadata, bonus claim PDA] for the extra NFT
+    ix.accounts.push(AccountMeta::new_readonly(bonus.token_account, false));
+    ix.accounts.push(AccountMeta::new_readonly(bonus.metadata, false));
+    ix.accounts.push(AccountMeta::new(bonus.bonus_claim, false));
+    ix
+}
+
+// Lamports sent to an NFT's bonus claim PDA ahead of time don't stop its holder from claiming
+// the bonus; the claim still counts once per giveaway
+#[tokio::test]
+async fn prefunded_bonus_claim_still_grants_the_bonus() {
+    let creator = Keypair::new();
+    let holder = Keypair::new();
+    let mut ctx = program_test(&[&creator, &holder]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let collection = Pubkey::new_unique();
+    let gate = collection_nft(&mut ctx, &holder, collection).await;
+    let bonus = collection_nft(&mut ctx, &holder, collection).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    let mut args = create_args(1, now, 1, 2);
+    args.entry_gate = Some(EntryGate::Collection { collection, bonus_tickets_per_nft: BONUS_TICKETS });
+    create_giveaway(&mut ctx, &creator, args).await;
+
+    let payer = ctx.payer.pubkey();
+    let prefund = ctx.banks_client.get_rent().await.unwrap().minimum_balance(0);
+    send(&mut ctx, &[system_instruction::transfer(&payer, &bonus.bonus_claim, prefund)], &[]).await.unwrap();
+
+    send(&mut ctx, &[gated_entry_ix(0, &holder, &gate, &bonus)], &[&holder]).await.unwrap();
+    let entry: GiveawayEntry = fetch(&mut ctx, entry_address(1, 0)).await;
+    assert_eq!(entry.bonus_tickets, BONUS_TICKETS as u64);
+    assert_eq!(entry.count, 1 + BONUS_TICKETS as u64);
+    let claim: NftBonus = fetch(&mut ctx, bonus.bonus_claim).await;
+    assert_eq!(claim.payer, holder.pubkey());
+
+    let result = send(&mut ctx, &[gated_entry_ix(entry.count, &holder, &gate, &bonus)], &[&holder]).await;
+    assert_givefi_error(result, GivefiError::NftBonusAlreadyClaimed);
+}
+
+// A bonus claim only has to outlive the giveaway; afterwards its rent goes back to whoever paid it
+#[tokio::test]
+async fn bonus_claim_closes_once_the_giveaway_is_over() {
+    let creator = Keypair::new();
+    let holder = Keypair::new();
+    let mut ctx = program_test(&[&creator, &holder]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let collection = Pubkey::new_unique();
+    let gate = collection_nft(&mut ctx, &holder, collection).await;
+    let bonus = collection_nft(&mut ctx, &holder, collection).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    let mut args = create_args(1, now, 1, 2);
+    args.entry_gate = Some(EntryGate::Collection { collection, bonus_tickets_per_nft: BONUS_TICKETS });
+    create_giveaway(&mut ctx, &creator, args).await;
+    send(&mut ctx, &[gated_entry_ix(0, &holder, &gate, &bonus)], &[&holder]).await.unwrap();
+
+    let close = givefi_ix(
+        accounts::CloseNftBonus {
+            giveaway: giveaway_address(1),
+            nft_bonus: bonus.bonus_claim,
+            payer: holder.pubkey(),
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CloseNftBonus {},
+    );
+    let result = send(&mut ctx, std::slice::from_ref(&close), &[]).await;
+    assert_givefi_error(result, GivefiError::GiveawayStillActive);
+
+    let cancel = givefi_ix(
+        accounts::CancelGiveaway {
+            giveaway: giveaway_address(1),
+            program_state: program_state(),
+            canceller: creator.pubkey(),
+            creator: creator.pubkey(),
+            prize_vault: None,
+            creator_prize_account: None,
+            prize_mint: None,
+            token_program: None,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CancelGiveaway {},
+    );
+    send(&mut ctx, &[cancel], &[&creator]).await.unwrap();
+
+    let rent = lamports(&mut ctx, bonus.bonus_claim).await;
+    let holder_before = lamports(&mut ctx, holder.pubkey()).await;
+    send(&mut ctx, &[close], &[]).await.unwrap();
+    assert!(!account_exists(&mut ctx, bonus.bonus_claim).await);
+    assert_eq!(lamports(&mut ctx, holder.pubkey()).await - holder_before, rent);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::*;
+use givefi_contract::{GivefiError, Giveaway, GiveawayStatus, RandomnessRequest};
+use solana_sdk::signature::{Keypair, Signer};
+
+#[tokio::test]
+async fn mock_oracle_fulfils_a_randomness_request() {
+    let creator = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let mut wallets = vec![&creator];
+    wallets.extend(&entrants);
+    let mut ctx = program_test(&wallets).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    set_oracle_program(&mut ctx, mock_oracle::ID).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    create_giveaway(&mut ctx, &creator, create_args(1, now, 2, 3)).await;
+    for (ticket, entrant) in entrants.iter().enumerate() {
+        enter_sol(&mut ctx, 1, ticket as u64, entrant).await;
+    }
+    end_early(&mut ctx, 1, &creator).await;
+    advance(&mut ctx, 1, None).await;
+
+    send(&mut ctx, &[request_randomness_ix(1, &creator)], &[&creator]).await.unwrap();
+    let request: RandomnessRequest = fetch(&mut ctx, randomness_request_address(1)).await;
+    assert_eq!(request.oracle_program, mock_oracle::ID);
+    assert!(!request.fulfilled);
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::AwaitingRandomness);
+
+    let payer = ctx.payer.pubkey();
+    send(&mut ctx, &[oracle_fulfill_ix(1, payer, [9; 32])], &[]).await.unwrap();
+
+    let request: RandomnessRequest = fetch(&mut ctx, randomness_request_address(1)).await;
+    assert!(request.fulfilled);
+    assert_eq!(request.result, [9; 32]);
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Drawn);
+    assert_eq!(giveaway.winners.len(), 2);
+    assert_ne!(giveaway.winners[0], giveaway.winners[1]);
+    assert!(giveaway.winners.iter().all(|&winner| winner < 3));
+
+    // A request is fulfilled once; a second result can't replace the winners
+    advance(&mut ctx, 1, None).await;
+    let result = send(&mut ctx, &[oracle_fulfill_ix(1, payer, [10; 32])], &[]).await;
+    assert_givefi_error(result, GivefiError::RandomnessAlreadyFulfilled);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::giveaway_fixture;
+use givefi_contract::{math, GiveawayStatus, JackpotSplit, PrizeTier, VaultLedger, BPS_DENOMINATOR};
+use proptest::prelude::*;
+
+// Up to 5 tiers of 1-4 winners whose shares add up to exactly 10_000 bps
+fn prize_tiers() -> impl Strategy<Value = Vec<PrizeTier>> {
+    prop::collection::vec((1u8..=4, 1u64..=100), 1..=5).prop_map(|raw| {
+        let weight: u64 = raw.iter().map(|(_, weight)| weight).sum();
+        let mut remaining = BPS_DENOMINATOR;
+        let last = raw.len() - 1;
+        raw.iter()
+            .enumerate()
+            .map(|(index, &(count, share))| {
+                let share_bps = if index == last { remaining } else { share * BPS_DENOMINATOR / weight };
+                remaining -= share_bps;
+                PrizeTier { count, share_bps: share_bps as u16, description: String::new() }
+            })
+            .collect()
+    })
+}
+
+fn jackpot_split() -> impl Strategy<Value = JackpotSplit> {
+    (0u16..=10_000).prop_flat_map(|winner_bps| {
+        (0u16..=10_000 - winner_bps).prop_map(move |creator_bps| JackpotSplit {
+            winner_bps,
+            creator_bps,
+            treasury_bps: 10_000 - winner_bps - creator_bps,
+        })
+    })
+}
+
+// Ticket counts and prices a real giveaway sees, mixed with arbitrary ones whose pool overflows
+fn ticket_count() -> impl Strategy<Value = u64> {
+    prop_oneof![1u64..=100_000, any::<u64>()]
+}
+
+fn entry_cost() -> impl Strategy<Value = u64> {
+    prop_oneof![1u64..=1_000_000_000_000, any::<u64>()]
+}
+
+proptest! {
+    #[test]
+    fn mul_fails_exactly_when_the_product_overflows(a: u64, b: u64) {
+        let wide = a as u128 * b as u128;
+        match math::mul(a, b) {
+            Ok(product) => prop_assert_eq!(product as u128, wide),
+            Err(_) => prop_assert!(wide > u64::MAX as u128),
+        }
+    }
+
+    #[test]
+    fn mul_div_matches_wide_arithmetic(value: u64, numerator in 0u64..=10_000, denominator in 1u64..=10_000) {
+        let expected = value as u128 * numerator as u128 / denominator as u128;
+        match math::mul_div(value, numerator, denominator) {
+            Ok(result) => prop_assert_eq!(result as u128, expected),
+            Err(_) => prop_assert!(expected > u64::MAX as u128),
+        }
+    }
+
+    #[test]
+    fn jackpot_split_conserves_every_lamport(total: u64, split in jackpot_split()) {
+        let (winner, creator, treasury) = split.apply(total).unwrap();
+        prop_assert_eq!(winner as u128 + creator as u128 + treasury as u128, total as u128);
+    }
+
+    // Draws winners for a fuzzed ticket count and price, sells the tickets as bulk entries and
+    // has every winning entry claim its jackpot through the helpers claim_jackpot uses
+    #[test]
+    fn jackpot_claims_never_pay_out_more_than_the_pool(
+        tiers in prize_tiers(),
+        entries in ticket_count(),
+        entry_cost in entry_cost(),
+        split in jackpot_split(),
+        commitment_slashed: bool,
+        picks in prop::collection::vec(any::<u64>(), 20),
+        cuts in prop::collection::vec(any::<u64>(), 0..8),
+    ) {
+        let mut giveaway = giveaway_fixture(entries, tiers);
+        giveaway.entry_cost_sol = entry_cost;
+        giveaway.jackpot_split = split;
+        giveaway.commitment_slashed = commitment_slashed;
+        let winners = giveaway.total_winners() as u64;
+        prop_assume!(entries >= winners);
+
+        let Ok(pool) = giveaway.sol_pool() else {
+            prop_assert!(entries as u128 * entry_cost as u128 > u64::MAX as u128);
+            return Ok(());
+        };
+        prop_assert_eq!(pool as u128, entries as u128 * entry_cost as u128);
+
+        for pick in picks.into_iter().take(winners as usize) {
+            let mut ticket = pick % entries;
+            while giveaway.winners.contains(&ticket) {
+                ticket = (ticket + 1) % entries;
+            }
+            giveaway.winners.push(ticket);
+        }
+        giveaway.status = GiveawayStatus::Drawn;
+
+        // Consecutive ticket ranges, so one entry may hold winners of several tiers
+        let mut bounds: Vec<u64> = cuts.into_iter().map(|cut| cut % entries).collect();
+        bounds.extend([0, entries]);
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let mut ledger = VaultLedger { collected: pool, ..VaultLedger::default() };
+        let mut vault = pool;
+        let mut claimed = 0;
+        for range in bounds.windows(2) {
+            let won = giveaway.tiers_won(range[0], range[1] - range[0]).unwrap();
+            if won.is_empty() {
+                continue;
+            }
+            claimed += won.len() as u64;
+
+            let shares = giveaway.claim_shares(pool, &won).unwrap();
+            prop_assert_eq!(shares.winner as u128 + shares.creator as u128 + shares.treasury as u128, shares.slice as u128);
+            if commitment_slashed {
+                prop_assert_eq!(shares.creator, 0);
+            }
+            prop_assert!(shares.slice <= vault);
+            vault -= shares.slice;
+            ledger.book_jackpot(&shares).unwrap();
+            prop_assert_eq!(ledger.held().unwrap(), vault);
+        }
+        prop_assert_eq!(claimed, winners);
+
+        // Only rounding dust stays behind: less than one lamport per winner
+        prop_assert!(vault < winners);
+    }
+}
@@ -0,0 +1,214 @@
+mod common;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use common::*;
+use givefi_contract::{accounts, instruction, GivefiError, Giveaway, GiveawayStatus, DRAW_SLOT_DELAY, REVEAL_WINDOW_SECS};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::{Keypair, Signer};
+
+fn settle_ix(id: u64, creator: &Keypair) -> Instruction {
+    givefi_ix(
+        accounts::SettleRandomness {
+            giveaway: giveaway_address(id),
+            randomness_request: randomness_request_address(id),
+            program_state: program_state(),
+            authority: creator.pubkey(),
+            slot_hashes: sysvar::slot_hashes::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::SettleRandomnessAndPickWinner { seed_preimage: SEED_PREIMAGE },
+    )
+}
+
+fn fallback_ix(id: u64, caller: &Keypair) -> Instruction {
+    givefi_ix(
+        accounts::DrawWinner {
+            giveaway: giveaway_address(id),
+            program_state: program_state(),
+            authority: caller.pubkey(),
+            slot_hashes: sysvar::slot_hashes::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::DrawWinnerFallback {},
+    )
+}
+
+fn record_ix(id: u64) -> Instruction {
+    givefi_ix(
+        accounts::RecordDrawSlot {
+            giveaway: giveaway_address(id),
+            slot_hashes: sysvar::slot_hashes::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::RecordDrawSlot {},
+    )
+}
+
+// Creates giveaway 1 for two entrants and ends it, ready for the creator's randomness request
+async fn ended_giveaway(ctx: &mut ProgramTestContext, creator: &Keypair, entrants: &[Keypair; 2]) -> i64 {
+    let now = clock(ctx).await.unix_timestamp;
+    let args = create_args(1, now, 1, 2);
+    let end_timestamp = args.end_timestamp;
+    create_giveaway(ctx, creator, args).await;
+    enter_sol(ctx, 1, 0, &entrants[0]).await;
+    enter_sol(ctx, 1, 1, &entrants[1]).await;
+    end_early(ctx, 1, creator).await;
+    advance(ctx, 1, None).await;
+    end_timestamp
+}
+
+#[tokio::test]
+async fn reveal_waits_for_the_pinned_slot() {
+    let creator = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new()];
+    let mut ctx = program_test(&[&creator, &entrants[0], &entrants[1]]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    ended_giveaway(&mut ctx, &creator, &entrants).await;
+
+    // Without an oracle the request starts the reveal clock, which is the authority's alone
+    let result = send(&mut ctx, &[request_randomness_ix(1, &entrants[0])], &[&entrants[0]]).await;
+    assert_givefi_error(result, GivefiError::InvalidOwner);
+
+    let request_slot = clock(&mut ctx).await.slot;
+    send(&mut ctx, &[request_randomness_ix(1, &creator)], &[&creator]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.recorded_slot, request_slot + DRAW_SLOT_DELAY);
+
+    // The pinned slot's hash doesn't exist yet, so the authority can't try its reveal against it
+    advance(&mut ctx, 1, None).await;
+    assert_givefi_error(send(&mut ctx, &[settle_ix(1, &creator)], &[&creator]).await, GivefiError::SlotHashNotReady);
+
+    pass_slot(&mut ctx, giveaway.recorded_slot).await;
+    send(&mut ctx, &[settle_ix(1, &creator)], &[&creator]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Drawn);
+    assert_eq!(giveaway.winners.len(), 1);
+    assert_ne!(giveaway.recorded_slot_hash, [0; 32]);
+}
+
+#[tokio::test]
+async fn fallback_pins_a_slot_before_drawing() {
+    let creator = Keypair::new();
+    let cranker = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new()];
+    let mut ctx = program_test(&[&creator, &cranker, &entrants[0], &entrants[1]]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    let args = create_args(1, now, 1, 2);
+    let end_timestamp = args.end_timestamp;
+    create_giveaway(&mut ctx, &creator, args).await;
+    enter_sol(&mut ctx, 1, 0, &entrants[0]).await;
+    enter_sol(&mut ctx, 1, 1, &entrants[1]).await;
+
+    // Nobody requested randomness: the first fallback call only pins a future slot
+    advance(&mut ctx, 1, Some(end_timestamp + REVEAL_WINDOW_SECS)).await;
+    let pinned_at = clock(&mut ctx).await.slot;
+    send(&mut ctx, &[fallback_ix(1, &cranker)], &[&cranker]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Closed);
+    assert!(giveaway.winners.is_empty());
+    assert_eq!(giveaway.recorded_slot, pinned_at + DRAW_SLOT_DELAY);
+
+    advance(&mut ctx, 1, None).await;
+    assert_givefi_error(send(&mut ctx, &[fallback_ix(1, &cranker)], &[&cranker]).await, GivefiError::SlotHashNotReady);
+
+    pass_slot(&mut ctx, giveaway.recorded_slot).await;
+    send(&mut ctx, &[fallback_ix(1, &cranker)], &[&cranker]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Drawn);
+    assert_eq!(giveaway.winners.len(), 1);
+    // The authority never revealed, so its jackpot share is forfeited
+    assert!(giveaway.commitment_slashed);
+}
+
+// Once anyone records the pinned slot's hash, the reveal no longer depends on SlotHashes, so the
+// authority gets the whole window rather than the ~512 slots the sysvar keeps
+#[tokio::test]
+async fn recorded_slot_hash_keeps_the_reveal_open() {
+    let creator = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new()];
+    let mut ctx = program_test(&[&creator, &entrants[0], &entrants[1]]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    ended_giveaway(&mut ctx, &creator, &entrants).await;
+    send(&mut ctx, &[request_randomness_ix(1, &creator)], &[&creator]).await.unwrap();
+    assert_givefi_error(send(&mut ctx, &[record_ix(1)], &[]).await, GivefiError::SlotHashNotReady);
+
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    pass_slot(&mut ctx, giveaway.recorded_slot).await;
+    send(&mut ctx, &[record_ix(1)], &[]).await.unwrap();
+    let recorded: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_ne!(recorded.recorded_slot_hash, [0; 32]);
+    advance(&mut ctx, 1, None).await;
+    assert_givefi_error(send(&mut ctx, &[record_ix(1)], &[]).await, GivefiError::DrawSlotAlreadyRecorded);
+
+    age_out_slot_hashes(&mut ctx).await;
+    send(&mut ctx, &[settle_ix(1, &creator)], &[&creator]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Drawn);
+    assert_eq!(giveaway.recorded_slot_hash, recorded.recorded_slot_hash);
+}
+
+// The fallback draws from the slot the request pinned. If that hash aged out before anyone
+// recorded it, only the platform may pin a replacement; anyone else could have seen the lost hash
+// and be waiting for a draw they like better.
+#[tokio::test]
+async fn only_the_platform_replaces_an_aged_out_pin() {
+    let creator = Keypair::new();
+    let cranker = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new()];
+    let mut ctx = program_test(&[&creator, &cranker, &entrants[0], &entrants[1]]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let end_timestamp = ended_giveaway(&mut ctx, &creator, &entrants).await;
+    send(&mut ctx, &[request_randomness_ix(1, &creator)], &[&creator]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    pass_slot(&mut ctx, giveaway.recorded_slot).await;
+    age_out_slot_hashes(&mut ctx).await;
+
+    advance(&mut ctx, 1, Some(end_timestamp + REVEAL_WINDOW_SECS)).await;
+    let result = send(&mut ctx, &[fallback_ix(1, &cranker)], &[&cranker]).await;
+    assert_givefi_error(result, GivefiError::SlotHashUnavailable);
+
+    let platform = ctx.payer.insecure_clone();
+    send(&mut ctx, &[fallback_ix(1, &platform)], &[]).await.unwrap();
+    let repinned: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(repinned.status, GiveawayStatus::AwaitingRandomness);
+    assert!(repinned.recorded_slot > giveaway.recorded_slot);
+
+    pass_slot(&mut ctx, repinned.recorded_slot).await;
+    send(&mut ctx, &[fallback_ix(1, &cranker)], &[&cranker]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Drawn);
+    assert!(giveaway.commitment_slashed);
+}
+
+// The slash follows what request_randomness asked for, not which accounts the fallback's caller
+// passes: an oracle that never answers doesn't cost the creator its share
+#[tokio::test]
+async fn fallback_after_an_unanswered_oracle_request_is_not_slashed() {
+    let creator = Keypair::new();
+    let cranker = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new()];
+    let mut ctx = program_test(&[&creator, &cranker, &entrants[0], &entrants[1]]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    set_oracle_program(&mut ctx, mock_oracle::ID).await;
+    let end_timestamp = ended_giveaway(&mut ctx, &creator, &entrants).await;
+    send(&mut ctx, &[request_randomness_ix(1, &cranker)], &[&cranker]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert!(giveaway.oracle_requested);
+
+    pass_slot(&mut ctx, giveaway.recorded_slot).await;
+    advance(&mut ctx, 1, Some(end_timestamp + REVEAL_WINDOW_SECS)).await;
+    send(&mut ctx, &[fallback_ix(1, &cranker)], &[&cranker]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Drawn);
+    assert!(!giveaway.commitment_slashed);
+}
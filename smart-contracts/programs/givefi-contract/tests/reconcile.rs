@@ -0,0 +1,152 @@
+mod common;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use givefi_contract::{
+    accounts, instruction, tier_slice, GivefiError, Giveaway, GiveawayStatus, DEFAULT_JACKPOT_SPLIT,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+
+fn reconcile_ix(id: u64, creator: &Keypair, treasury: Pubkey) -> Instruction {
+    givefi_ix(
+        accounts::ReconcileVault {
+            giveaway: giveaway_address(id),
+            giveaway_vault: vault_address(id),
+            program_state: program_state(),
+            treasury_wallet: treasury,
+            creator: creator.pubkey(),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::ReconcileVault {},
+    )
+}
+
+fn claim_jackpot_ix(ticket: u64, winner: &Keypair, creator: &Keypair, treasury: Pubkey) -> Instruction {
+    givefi_ix(
+        accounts::ClaimJackpot {
+            giveaway: giveaway_address(1),
+            entry: entry_address(1, ticket),
+            participant: participant_address(1, &winner.pubkey()),
+            program_state: program_state(),
+            giveaway_vault: vault_address(1),
+            user_token_account: None,
+            owner_token_account: None,
+            treasury_token_account: None,
+            giveaway_token_vault: None,
+            give_mint: None,
+            user: winner.pubkey(),
+            owner: creator.pubkey(),
+            treasury_wallet: treasury,
+            payer: winner.pubkey(),
+            token_program: None,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::ClaimJackpot {},
+    )
+}
+
+async fn rent_reserve(ctx: &mut ProgramTestContext) -> u64 {
+    ctx.banks_client.get_sysvar::<Rent>().await.unwrap().minimum_balance(0)
+}
+
+// Both winners take the prize on a giveaway without the jackpot option, so the whole pool is
+// still in the vault at settlement: reconcile splits it instead of sweeping it to the treasury
+#[tokio::test]
+async fn settled_pool_is_split_not_swept() {
+    let creator = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let treasury = Keypair::new().pubkey();
+    let mut wallets = vec![&creator];
+    wallets.extend(&entrants);
+    let mut ctx = program_test(&wallets).start_with_context().await;
+
+    initialize(&mut ctx, treasury).await;
+    set_oracle_program(&mut ctx, mock_oracle::ID).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    let mut args = create_args(1, now, 2, 3);
+    args.jackpot_option_enabled = false;
+    // Two slices of a 300_000_003 lamport pool leave one lamport of rounding dust
+    args.entry_cost_sol = ENTRY_COST + 1;
+    let giveaway = drawn_giveaway(&mut ctx, &creator, &entrants, args).await;
+
+    for &ticket in &giveaway.winners {
+        let winner = &entrants[ticket as usize];
+        send(&mut ctx, &[claim_prize_ix(1, ticket, winner)], &[winner]).await.unwrap();
+    }
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Settled);
+
+    let stray = 5_000;
+    let payer = ctx.payer.pubkey();
+    send(&mut ctx, &[system_instruction::transfer(&payer, &vault_address(1), stray)], &[]).await.unwrap();
+
+    let creator_before = lamports(&mut ctx, creator.pubkey()).await;
+    let treasury_before = lamports(&mut ctx, treasury).await;
+    send(&mut ctx, &[reconcile_ix(1, &creator, treasury)], &[]).await.unwrap();
+
+    let pool = 3 * (ENTRY_COST + 1);
+    let (winner, creator_share, treasury_share) = DEFAULT_JACKPOT_SPLIT.apply(pool - 1).unwrap();
+    let reserve = rent_reserve(&mut ctx).await;
+    assert_eq!(lamports(&mut ctx, creator.pubkey()).await - creator_before, winner + creator_share + reserve);
+    assert_eq!(lamports(&mut ctx, treasury).await - treasury_before, treasury_share + 1 + stray);
+
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.sol_ledger.held().unwrap(), 0);
+    assert_eq!(giveaway.sol_ledger.swept, stray);
+    assert_eq!(lamports(&mut ctx, vault_address(1)).await, 0);
+
+    advance(&mut ctx, 1, None).await;
+    let result = send(&mut ctx, &[reconcile_ix(1, &creator, treasury)], &[]).await;
+    assert_givefi_error(result, GivefiError::NothingToReconcile);
+}
+
+// Jackpot slices round down, so after every winner has claimed the vault still holds the dust.
+// The rent reserve keeps those last claims from leaving it rent-paying, and reconcile then sweeps
+// the dust and returns the reserve, emptying the vault.
+#[tokio::test]
+async fn last_jackpot_claim_leaves_dust_above_the_reserve() {
+    let creator = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let treasury = Keypair::new().pubkey();
+    let mut wallets = vec![&creator];
+    wallets.extend(&entrants);
+    let mut ctx = program_test(&wallets).start_with_context().await;
+
+    initialize(&mut ctx, treasury).await;
+    set_oracle_program(&mut ctx, mock_oracle::ID).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    let mut args = create_args(1, now, 2, 3);
+    args.entry_cost_sol = ENTRY_COST + 1;
+    let giveaway = drawn_giveaway(&mut ctx, &creator, &entrants, args).await;
+    let reserve = rent_reserve(&mut ctx).await;
+    assert_eq!(giveaway.sol_ledger.reserve, reserve);
+
+    for &ticket in &giveaway.winners {
+        let winner = &entrants[ticket as usize];
+        send(&mut ctx, &[claim_jackpot_ix(ticket, winner, &creator, treasury)], &[winner]).await.unwrap();
+    }
+    let pool = 3 * (ENTRY_COST + 1);
+    let dust = pool - 2 * tier_slice(pool, &giveaway.prize_tiers[0]).unwrap();
+    assert!(dust > 0);
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Settled);
+    assert_eq!(lamports(&mut ctx, vault_address(1)).await, reserve + dust);
+
+    let creator_before = lamports(&mut ctx, creator.pubkey()).await;
+    let treasury_before = lamports(&mut ctx, treasury).await;
+    send(&mut ctx, &[reconcile_ix(1, &creator, treasury)], &[]).await.unwrap();
+    assert_eq!(lamports(&mut ctx, creator.pubkey()).await - creator_before, reserve);
+    assert_eq!(lamports(&mut ctx, treasury).await - treasury_before, dust);
+    assert_eq!(lamports(&mut ctx, vault_address(1)).await, 0);
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.sol_ledger.reserve, 0);
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use givefi_contract::{accounts, instruction, CreatorIndex, GivefiError, Participant};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+fn creator_index_address(creator: &Keypair) -> Pubkey {
+    pda(&[b"creator_index", creator.pubkey().as_ref()])
+}
+
+fn close_randomness_request_ix(id: u64, payer: Pubkey) -> Instruction {
+    givefi_ix(
+        accounts::CloseRandomnessRequest {
+            giveaway: giveaway_address(id),
+            randomness_request: randomness_request_address(id),
+            payer,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CloseRandomnessRequest {},
+    )
+}
+
+// A wallet's Participant account outlives the refund of its first entry and closes back to the
+// wallet with the second
+#[tokio::test]
+async fn participant_closes_with_the_wallets_last_entry() {
+    let creator = Keypair::new();
+    let entrant = Keypair::new();
+    let cranker = Keypair::new();
+    let mut ctx = program_test(&[&creator, &entrant, &cranker]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    create_giveaway(&mut ctx, &creator, create_args(1, now, 1, 2)).await;
+    enter_sol(&mut ctx, 1, 0, &entrant).await;
+    enter_sol(&mut ctx, 1, 1, &entrant).await;
+    let participant = participant_address(1, &entrant.pubkey());
+    let tally: Participant = fetch(&mut ctx, participant).await;
+    assert_eq!(tally.entries, 2);
+
+    let cancel = givefi_ix(
+        accounts::CancelGiveaway {
+            giveaway: giveaway_address(1),
+            program_state: program_state(),
+            canceller: creator.pubkey(),
+            creator: creator.pubkey(),
+            prize_vault: None,
+            creator_prize_account: None,
+            prize_mint: None,
+            token_program: None,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CancelGiveaway {},
+    );
+    send(&mut ctx, &[cancel], &[&creator]).await.unwrap();
+
+    let participant_rent = lamports(&mut ctx, participant).await;
+    for ticket in [0, 1] {
+        let mut crank = givefi_ix(
+            accounts::CrankRefunds {
+                giveaway: giveaway_address(1),
+                giveaway_vault: vault_address(1),
+                giveaway_token_vault: None,
+                give_mint: None,
+                program_state: program_state(),
+                cranker: cranker.pubkey(),
+                token_program: None,
+                system_program: system_program::ID,
+                event_authority: event_authority(),
+                program: givefi_contract::ID,
+            },
+            instruction::CrankRefunds {},
+        );
+        // [entry, owner, rent payer, participant]
+        crank.accounts.push(AccountMeta::new(entry_address(1, ticket), false));
+        crank.accounts.push(AccountMeta::new(entrant.pubkey(), false));
+        crank.accounts.push(AccountMeta::new(entrant.pubkey(), false));
+        crank.accounts.push(AccountMeta::new(participant, false));
+        let entry_rent = lamports(&mut ctx, entry_address(1, ticket)).await;
+        let entrant_before = lamports(&mut ctx, entrant.pubkey()).await;
+        send(&mut ctx, &[crank], &[&cranker]).await.unwrap();
+
+        let returned = lamports(&mut ctx, entrant.pubkey()).await - entrant_before;
+        if ticket == 0 {
+            let tally: Participant = fetch(&mut ctx, participant).await;
+            assert_eq!(tally.entries, 1);
+            assert_eq!(returned, ENTRY_COST + entry_rent);
+        } else {
+            assert!(!account_exists(&mut ctx, participant).await);
+            assert_eq!(returned, ENTRY_COST + entry_rent + participant_rent);
+        }
+    }
+}
+
+// The randomness request is kept until the draw is done, then closes back to whoever paid for it
+#[tokio::test]
+async fn randomness_request_closes_once_drawn() {
+    let creator = Keypair::new();
+    let requester = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new()];
+    let mut ctx = program_test(&[&creator, &requester, &entrants[0], &entrants[1]]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    set_oracle_program(&mut ctx, mock_oracle::ID).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    create_giveaway(&mut ctx, &creator, create_args(1, now, 1, 2)).await;
+    enter_sol(&mut ctx, 1, 0, &entrants[0]).await;
+    enter_sol(&mut ctx, 1, 1, &entrants[1]).await;
+    end_early(&mut ctx, 1, &creator).await;
+    advance(&mut ctx, 1, None).await;
+    send(&mut ctx, &[request_randomness_ix(1, &requester)], &[&requester]).await.unwrap();
+
+    let result = send(&mut ctx, &[close_randomness_request_ix(1, requester.pubkey())], &[]).await;
+    assert_givefi_error(result, GivefiError::GiveawayStillActive);
+
+    let payer = ctx.payer.pubkey();
+    send(&mut ctx, &[oracle_fulfill_ix(1, payer, [9; 32])], &[]).await.unwrap();
+    let result = send(&mut ctx, &[close_randomness_request_ix(1, creator.pubkey())], &[]).await;
+    assert_givefi_error(result, GivefiError::InvalidRentPayer);
+
+    let rent = lamports(&mut ctx, randomness_request_address(1)).await;
+    let requester_before = lamports(&mut ctx, requester.pubkey()).await;
+    send(&mut ctx, &[close_randomness_request_ix(1, requester.pubkey())], &[]).await.unwrap();
+    assert!(!account_exists(&mut ctx, randomness_request_address(1)).await);
+    assert_eq!(lamports(&mut ctx, requester.pubkey()).await - requester_before, rent);
+}
+
+// A creator can drop its index for the rent; the next giveaway it creates starts a new one
+#[tokio::test]
+async fn creator_index_closes_to_its_creator() {
+    let creator = Keypair::new();
+    let mut ctx = program_test(&[&creator]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    create_giveaway(&mut ctx, &creator, create_args(1, now, 1, 2)).await;
+
+    let close = givefi_ix(
+        accounts::CloseCreatorIndex {
+            creator_index: creator_index_address(&creator),
+            creator: creator.pubkey(),
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CloseCreatorIndex {},
+    );
+    send(&mut ctx, &[close], &[&creator]).await.unwrap();
+    assert!(!account_exists(&mut ctx, creator_index_address(&creator)).await);
+
+    create_giveaway(&mut ctx, &creator, create_args(2, now, 1, 2)).await;
+    let index: CreatorIndex = fetch(&mut ctx, creator_index_address(&creator)).await;
+    assert_eq!(index.giveaway_ids, vec![2]);
+}
@@ -0,0 +1,422 @@
+mod common;
+
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::{token, token_2022};
+use common::*;
+use givefi_contract::{
+    accounts, instruction, tier_slice, GivefiError, Giveaway, GiveawayStatus, PaymentType, PAUSE_CREATION,
+};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+const GIVE_COST: u64 = 1_000_000;
+const USDC_PRICE: u64 = 2_000_000;
+// 1% on every GIVE transfer, in and out of the vaults
+const GIVE_FEE_BPS: u16 = 100;
+
+fn after_fee(amount: u64) -> u64 {
+    amount - (amount * GIVE_FEE_BPS as u64).div_ceil(10_000)
+}
+
+fn token_vault_address(id: u64) -> Pubkey {
+    pda(&[b"token_vault", &id.to_le_bytes()])
+}
+
+fn mint_vault_address(id: u64, mint: &Pubkey) -> Pubkey {
+    pda(&[b"mint_vault", &id.to_le_bytes(), mint.as_ref()])
+}
+
+fn add_entry_mint_ix(id: u64, mint: Pubkey, creator: &Keypair, token_program: Pubkey, price: u64) -> Instruction {
+    givefi_ix(
+        accounts::AddEntryMint {
+            giveaway: giveaway_address(id),
+            mint,
+            mint_vault: mint_vault_address(id, &mint),
+            program_state: program_state(),
+            authority: creator.pubkey(),
+            token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::AddEntryMint { price },
+    )
+}
+
+fn create_with_give_ix(args: instruction::CreateGiveaway, creator: &Keypair, give_mint: Pubkey) -> Instruction {
+    let id = args.giveaway_id.unwrap();
+    givefi_ix(
+        accounts::CreateGiveaway {
+            program_state: program_state(),
+            giveaway: giveaway_address(id),
+            giveaway_vault: vault_address(id),
+            giveaway_token_vault: Some(token_vault_address(id)),
+            give_mint: Some(give_mint),
+            creator_index: pda(&[b"creator_index", creator.pubkey().as_ref()]),
+            authority: creator.pubkey(),
+            token_program: Some(token_2022::ID),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        args,
+    )
+}
+
+fn enter_give_ix(id: u64, entry_number: u64, user: &Keypair, user_token_account: Pubkey, give_mint: Pubkey) -> Instruction {
+    givefi_ix(
+        accounts::EnterGiveawayGive {
+            giveaway: giveaway_address(id),
+            entry: entry_address(id, entry_number),
+            participant: participant_address(id, &user.pubkey()),
+            user_token_account,
+            giveaway_token_vault: token_vault_address(id),
+            give_mint,
+            program_state: program_state(),
+            gate_token_account: None,
+            gate_metadata: None,
+            user: user.pubkey(),
+            token_program: token_2022::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::EnterGiveawayGive { allowlist_proof: None },
+    )
+}
+
+// GIVE on Token-2022 with a transfer fee and USDC on the classic token program, in one giveaway:
+// both pools are tallied net of fees and a jackpot claim pays each out through its own program
+#[tokio::test]
+async fn jackpot_pays_token_2022_give_and_classic_usdc_pools() {
+    let creator = Keypair::new();
+    let give_entrant = Keypair::new();
+    let usdc_entrant = Keypair::new();
+    let treasury = Keypair::new().pubkey();
+    let mut ctx = program_test(&[&creator, &give_entrant, &usdc_entrant]).start_with_context().await;
+
+    initialize(&mut ctx, treasury).await;
+    set_oracle_program(&mut ctx, mock_oracle::ID).await;
+    let give_mint = create_mint(&mut ctx, token_2022::ID, Some(MintExtension::TransferFee(GIVE_FEE_BPS, u64::MAX))).await;
+    let usdc_mint = create_mint(&mut ctx, token::ID, None).await;
+
+    let now = clock(&mut ctx).await.unix_timestamp;
+    let mut args = create_args(1, now, 2, 2);
+    args.entry_cost_give = Some(GIVE_COST);
+    let create = create_with_give_ix(args, &creator, give_mint);
+    let add_usdc = add_entry_mint_ix(1, usdc_mint, &creator, token::ID, USDC_PRICE);
+    send(&mut ctx, &[create, add_usdc], &[&creator]).await.unwrap();
+
+    let give_account = token_account(&mut ctx, token_2022::ID, give_mint, give_entrant.pubkey(), GIVE_COST).await;
+    send(&mut ctx, &[enter_give_ix(1, 0, &give_entrant, give_account, give_mint)], &[&give_entrant]).await.unwrap();
+
+    let usdc_account = token_account(&mut ctx, token::ID, usdc_mint, usdc_entrant.pubkey(), USDC_PRICE).await;
+    let enter_usdc = givefi_ix(
+        accounts::EnterGiveawayBulk {
+            giveaway: giveaway_address(1),
+            entry: entry_address(1, 1),
+            participant: participant_address(1, &usdc_entrant.pubkey()),
+            giveaway_vault: vault_address(1),
+            user_token_account: Some(usdc_account),
+            giveaway_token_vault: None,
+            mint_vault: Some(mint_vault_address(1, &usdc_mint)),
+            payment_mint: Some(usdc_mint),
+            program_state: program_state(),
+            gate_token_account: None,
+            gate_metadata: None,
+            user: usdc_entrant.pubkey(),
+            token_program: Some(token::ID),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::EnterGiveawayBulk { quantity: 1, payment_type: PaymentType::Spl { mint_index: 0 }, allowlist_proof: None },
+    );
+    send(&mut ctx, &[enter_usdc], &[&usdc_entrant]).await.unwrap();
+
+    // The GIVE pool only counts what reached the vault after the fee
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.give_pool, after_fee(GIVE_COST));
+    assert_eq!(token_balance(&mut ctx, token_vault_address(1)).await, giveaway.give_pool);
+    assert_eq!(giveaway.accepted_mints[0].pool, USDC_PRICE);
+
+    end_early(&mut ctx, 1, &creator).await;
+    advance(&mut ctx, 1, None).await;
+    let payer = ctx.payer.pubkey();
+    send(&mut ctx, &[request_randomness_ix(1, &creator), oracle_fulfill_ix(1, payer, [9; 32])], &[&creator])
+        .await
+        .unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Drawn);
+
+    // Two tickets, two winners: the GIVE entrant's ticket wins one slice of each pool
+    let give_accounts = [give_entrant.pubkey(), creator.pubkey(), treasury]
+        .map(|owner| get_associated_token_address_with_program_id(&owner, &give_mint, &token_2022::ID));
+    for owner in [creator.pubkey(), treasury] {
+        token_account(&mut ctx, token_2022::ID, give_mint, owner, 0).await;
+    }
+    let mut usdc_accounts = vec![usdc_mint, mint_vault_address(1, &usdc_mint)];
+    for owner in [give_entrant.pubkey(), creator.pubkey(), treasury] {
+        usdc_accounts.push(token_account(&mut ctx, token::ID, usdc_mint, owner, 0).await);
+    }
+    usdc_accounts.push(token::ID);
+
+    let mut claim = givefi_ix(
+        accounts::ClaimJackpot {
+            giveaway: giveaway_address(1),
+            entry: entry_address(1, 0),
+            participant: participant_address(1, &give_entrant.pubkey()),
+            program_state: program_state(),
+            giveaway_vault: vault_address(1),
+            user_token_account: Some(give_accounts[0]),
+            owner_token_account: Some(give_accounts[1]),
+            treasury_token_account: Some(give_accounts[2]),
+            giveaway_token_vault: Some(token_vault_address(1)),
+            give_mint: Some(give_mint),
+            user: give_entrant.pubkey(),
+            owner: creator.pubkey(),
+            treasury_wallet: treasury,
+            payer: give_entrant.pubkey(),
+            token_program: Some(token_2022::ID),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::ClaimJackpot {},
+    );
+    claim.accounts.extend(usdc_accounts.iter().enumerate().map(|(index, &address)| {
+        // Mint and token program are read-only; vault and destinations are written
+        if index == 0 || index == 5 { AccountMeta::new_readonly(address, false) } else { AccountMeta::new(address, false) }
+    }));
+    send(&mut ctx, &[claim], &[&give_entrant]).await.unwrap();
+
+    let tier = &giveaway.prize_tiers[0];
+    let (winner, creator_share, treasury_share) =
+        giveaway.jackpot_split.apply(tier_slice(giveaway.give_pool, tier).unwrap()).unwrap();
+    for (account, amount) in give_accounts.into_iter().zip([winner, creator_share, treasury_share]) {
+        assert_eq!(token_balance(&mut ctx, account).await, after_fee(amount));
+    }
+    let (winner, creator_share, treasury_share) =
+        giveaway.jackpot_split.apply(tier_slice(USDC_PRICE, tier).unwrap()).unwrap();
+    for (&account, amount) in usdc_accounts[2..5].iter().zip([winner, creator_share, treasury_share]) {
+        assert_eq!(token_balance(&mut ctx, account).await, amount);
+    }
+    assert_eq!(token_balance(&mut ctx, mint_vault_address(1, &usdc_mint)).await, USDC_PRICE - tier_slice(USDC_PRICE, tier).unwrap());
+}
+
+// Adding an entry currency is a creation-time change, so it stops under the creation pause and on
+// a frozen giveaway like the rest of setup
+#[tokio::test]
+async fn add_entry_mint_respects_pause_and_freeze() {
+    let creator = Keypair::new();
+    let mut ctx = program_test(&[&creator]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let usdc_mint = create_mint(&mut ctx, token::ID, None).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    create_giveaway(&mut ctx, &creator, create_args(1, now, 1, 2)).await;
+
+    let admin = |data: instruction::SetPaused| {
+        givefi_ix(
+            accounts::UpdateProgramState {
+                program_state: program_state(),
+                authority: ctx.payer.pubkey(),
+                event_authority: event_authority(),
+                program: givefi_contract::ID,
+            },
+            data,
+        )
+    };
+    let pause = admin(instruction::SetPaused { paused: PAUSE_CREATION });
+    let unpause = admin(instruction::SetPaused { paused: 0 });
+    send(&mut ctx, &[pause], &[]).await.unwrap();
+    let add_usdc = add_entry_mint_ix(1, usdc_mint, &creator, token::ID, USDC_PRICE);
+    let result = send(&mut ctx, std::slice::from_ref(&add_usdc), &[&creator]).await;
+    assert_givefi_error(result, GivefiError::ProgramPaused);
+
+    let freeze = givefi_ix(
+        accounts::FreezeGiveaway {
+            giveaway: giveaway_address(1),
+            program_state: program_state(),
+            authority: ctx.payer.pubkey(),
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::FreezeGiveaway {},
+    );
+    send(&mut ctx, &[unpause, freeze], &[]).await.unwrap();
+    let result = send(&mut ctx, &[add_usdc], &[&creator]).await;
+    assert_givefi_error(result, GivefiError::GiveawayFrozen);
+}
+
+// Vault payouts don't forward a hook's extra accounts, so a hooked mint is turned away wherever
+// a giveaway would start holding it
+#[tokio::test]
+async fn transfer_hook_mints_are_rejected() {
+    let creator = Keypair::new();
+    let mut ctx = program_test(&[&creator]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let hooked_mint = create_mint(&mut ctx, token_2022::ID, Some(MintExtension::TransferHook)).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+
+    let mut args = create_args(1, now, 1, 2);
+    args.entry_cost_give = Some(GIVE_COST);
+    let result = send(&mut ctx, &[create_with_give_ix(args, &creator, hooked_mint)], &[&creator]).await;
+    assert_givefi_error(result, GivefiError::TransferHookNotSupported);
+
+    create_giveaway(&mut ctx, &creator, create_args(1, now, 1, 2)).await;
+    let add_hooked = add_entry_mint_ix(1, hooked_mint, &creator, token_2022::ID, USDC_PRICE);
+    let result = send(&mut ctx, &[add_hooked], &[&creator]).await;
+    assert_givefi_error(result, GivefiError::TransferHookNotSupported);
+}
+
+// Interest only changes how an interest-bearing mint's amounts are displayed; the raw amount paid
+// in is what a refund pays back
+#[tokio::test]
+async fn interest_bearing_give_refunds_in_full() {
+    let creator = Keypair::new();
+    let entrant = Keypair::new();
+    let mut ctx = program_test(&[&creator, &entrant]).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    let give_mint = create_mint(&mut ctx, token_2022::ID, Some(MintExtension::InterestBearing(500))).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    let mut args = create_args(1, now, 1, 2);
+    args.entry_cost_give = Some(GIVE_COST);
+    send(&mut ctx, &[create_with_give_ix(args, &creator, give_mint)], &[&creator]).await.unwrap();
+
+    let give_account = token_account(&mut ctx, token_2022::ID, give_mint, entrant.pubkey(), GIVE_COST).await;
+    send(&mut ctx, &[enter_give_ix(1, 0, &entrant, give_account, give_mint)], &[&entrant]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.give_pool, GIVE_COST);
+
+    let cancel = givefi_ix(
+        accounts::CancelGiveaway {
+            giveaway: giveaway_address(1),
+            program_state: program_state(),
+            canceller: creator.pubkey(),
+            creator: creator.pubkey(),
+            prize_vault: None,
+            creator_prize_account: None,
+            prize_mint: None,
+            token_program: None,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CancelGiveaway {},
+    );
+    send(&mut ctx, &[cancel], &[&creator]).await.unwrap();
+    // A year on, the displayed balance has grown but the raw amount owed has not
+    advance(&mut ctx, 1, Some(now + 365 * 24 * 60 * 60)).await;
+
+    let refund = givefi_ix(
+        accounts::ClaimRefund {
+            giveaway: giveaway_address(1),
+            entry: entry_address(1, 0),
+            participant: participant_address(1, &entrant.pubkey()),
+            giveaway_vault: vault_address(1),
+            user_token_account: Some(give_account),
+            giveaway_token_vault: Some(token_vault_address(1)),
+            give_mint: Some(give_mint),
+            user: entrant.pubkey(),
+            payer: entrant.pubkey(),
+            token_program: Some(token_2022::ID),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::ClaimRefund {},
+    );
+    send(&mut ctx, &[refund], &[&entrant]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, give_account).await, GIVE_COST);
+    assert_eq!(token_balance(&mut ctx, token_vault_address(1)).await, 0);
+}
+
+// GIVE minted straight into the vault is not owed to any entrant: reconcile sweeps it to the
+// treasury and leaves the refunds the vault still owes in place
+#[tokio::test]
+async fn reconcile_sweeps_stray_give_to_the_treasury() {
+    let creator = Keypair::new();
+    let entrant = Keypair::new();
+    let treasury = Keypair::new().pubkey();
+    let mut ctx = program_test(&[&creator, &entrant]).start_with_context().await;
+
+    initialize(&mut ctx, treasury).await;
+    let give_mint = create_mint(&mut ctx, token_2022::ID, None).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    let mut args = create_args(1, now, 1, 2);
+    args.entry_cost_give = Some(GIVE_COST);
+    send(&mut ctx, &[create_with_give_ix(args, &creator, give_mint)], &[&creator]).await.unwrap();
+    let give_account = token_account(&mut ctx, token_2022::ID, give_mint, entrant.pubkey(), GIVE_COST).await;
+    send(&mut ctx, &[enter_give_ix(1, 0, &entrant, give_account, give_mint)], &[&entrant]).await.unwrap();
+
+    let cancel = givefi_ix(
+        accounts::CancelGiveaway {
+            giveaway: giveaway_address(1),
+            program_state: program_state(),
+            canceller: creator.pubkey(),
+            creator: creator.pubkey(),
+            prize_vault: None,
+            creator_prize_account: None,
+            prize_mint: None,
+            token_program: None,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CancelGiveaway {},
+    );
+    send(&mut ctx, &[cancel], &[&creator]).await.unwrap();
+
+    let stray = 5_000;
+    let payer = ctx.payer.pubkey();
+    let give_vault = token_vault_address(1);
+    let mint_stray =
+        token_2022::spl_token_2022::instruction::mint_to(&token_2022::ID, &give_mint, &give_vault, &payer, &[], stray)
+            .unwrap();
+    send(&mut ctx, &[mint_stray], &[]).await.unwrap();
+    let creator_give = token_account(&mut ctx, token_2022::ID, give_mint, creator.pubkey(), 0).await;
+    let treasury_give = token_account(&mut ctx, token_2022::ID, give_mint, treasury, 0).await;
+
+    let reconcile = |give_group: bool| {
+        let mut ix = givefi_ix(
+            accounts::ReconcileVault {
+                giveaway: giveaway_address(1),
+                giveaway_vault: vault_address(1),
+                program_state: program_state(),
+                treasury_wallet: treasury,
+                creator: creator.pubkey(),
+                system_program: system_program::ID,
+                event_authority: event_authority(),
+                program: givefi_contract::ID,
+            },
+            instruction::ReconcileVault {},
+        );
+        if give_group {
+            ix.accounts.extend([
+                AccountMeta::new_readonly(give_mint, false),
+                AccountMeta::new(token_vault_address(1), false),
+                AccountMeta::new(creator_give, false),
+                AccountMeta::new(treasury_give, false),
+                AccountMeta::new_readonly(token_2022::ID, false),
+            ]);
+        }
+        ix
+    };
+    let result = send(&mut ctx, &[reconcile(false)], &[]).await;
+    assert_givefi_error(result, GivefiError::GiveAccountsMissing);
+
+    send(&mut ctx, &[reconcile(true)], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, treasury_give).await, stray);
+    assert_eq!(token_balance(&mut ctx, creator_give).await, 0);
+    assert_eq!(token_balance(&mut ctx, token_vault_address(1)).await, GIVE_COST);
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.give_ledger.swept, stray);
+    assert_eq!(giveaway.give_ledger.held().unwrap(), GIVE_COST);
+
+    advance(&mut ctx, 1, None).await;
+    let result = send(&mut ctx, &[reconcile(true)], &[]).await;
+    assert_givefi_error(result, GivefiError::NothingToReconcile);
+}
@@ -0,0 +1,248 @@
+mod common;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+use common::*;
+use givefi_contract::{
+    accounts, instruction, tier_slice, ClaimDeadline, GivefiError, Giveaway, GiveawayEntry, GiveawayStatus,
+    TicketRange, UnclaimedPolicy,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+const WINDOW_SECS: i64 = 3_600;
+
+fn args(now: i64, policy: UnclaimedPolicy) -> instruction::CreateGiveaway {
+    let mut args = create_args(1, now, 1, 2);
+    args.claim_deadline = Some(ClaimDeadline { window_secs: WINDOW_SECS, policy });
+    args
+}
+
+async fn reclaim_ix(ctx: &mut ProgramTestContext, first_entry_number: u64, recipient: Option<Pubkey>) -> Instruction {
+    let entry: GiveawayEntry = fetch(ctx, entry_address(1, first_entry_number)).await;
+    givefi_ix(
+        accounts::ReclaimUnclaimed {
+            giveaway: giveaway_address(1),
+            entry: entry_address(1, first_entry_number),
+            participant: participant_address(1, &entry.user),
+            program_state: program_state(),
+            giveaway_vault: vault_address(1),
+            recipient,
+            prize_vault: None,
+            recipient_prize_account: None,
+            prize_mint: None,
+            payer: entry.payer,
+            token_program: None,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::ReclaimUnclaimed {},
+    )
+}
+
+fn close_losing_entry_ix(first_entry_number: u64, user: &Keypair) -> Instruction {
+    givefi_ix(
+        accounts::CloseLosingEntry {
+            giveaway: giveaway_address(1),
+            entry: entry_address(1, first_entry_number),
+            participant: participant_address(1, &user.pubkey()),
+            payer: user.pubkey(),
+            user: user.pubkey(),
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::CloseLosingEntry {},
+    )
+}
+
+// Past every winner's deadline
+async fn close_claim_window(ctx: &mut ProgramTestContext) {
+    let giveaway: Giveaway = fetch(ctx, giveaway_address(1)).await;
+    advance(ctx, 1, Some(*giveaway.winner_deadlines.iter().max().unwrap())).await;
+}
+
+fn redraw_ix(authority: Pubkey) -> Instruction {
+    givefi_ix(
+        accounts::RedrawWinners {
+            giveaway: giveaway_address(1),
+            program_state: program_state(),
+            authority,
+            slot_hashes: sysvar::slot_hashes::ID,
+            event_authority: event_authority(),
+            program: givefi_contract::ID,
+        },
+        instruction::RedrawWinners {},
+    )
+}
+
+// Runs the queued redraw once its pinned slot has passed
+async fn redraw(ctx: &mut ProgramTestContext) -> Giveaway {
+    let giveaway: Giveaway = fetch(ctx, giveaway_address(1)).await;
+    pass_slot(ctx, giveaway.redraw_slot).await;
+    let payer = ctx.payer.pubkey();
+    send(ctx, &[redraw_ix(payer)], &[]).await.unwrap();
+    fetch(ctx, giveaway_address(1)).await
+}
+
+// Each unclaimed winner is replaced by a ticket that neither won nor was forfeited, until none is
+// left and the share goes to the creator. Losers can't close their entries in the meantime, since
+// any of them may still be drawn.
+#[tokio::test]
+async fn redraws_skip_forfeited_tickets_then_route_to_creator() {
+    let creator = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let mut wallets = vec![&creator];
+    wallets.extend(&entrants);
+    let mut ctx = program_test(&wallets).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    set_oracle_program(&mut ctx, mock_oracle::ID).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    let giveaway = drawn_giveaway(&mut ctx, &creator, &entrants, args(now, UnclaimedPolicy::Redraw)).await;
+    let first_winner = giveaway.winners[0];
+
+    let loser = (first_winner + 1) % 3;
+    let result = send(&mut ctx, &[close_losing_entry_ix(loser, &entrants[loser as usize])], &[&entrants[loser as usize]]).await;
+    assert_givefi_error(result, GivefiError::RedrawPending);
+
+    let ix = reclaim_ix(&mut ctx, first_winner, None).await;
+    let result = send(&mut ctx, std::slice::from_ref(&ix), &[]).await;
+    assert_givefi_error(result, GivefiError::ClaimWindowOpen);
+
+    close_claim_window(&mut ctx).await;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.pending_redraws, vec![0]);
+    assert_eq!(giveaway.retired_tickets, vec![TicketRange { first_entry_number: first_winner, count: 1 }]);
+    assert!(!account_exists(&mut ctx, entry_address(1, first_winner)).await);
+
+    // The replacement waits for a slot that hadn't been produced at reclaim time, and nothing may
+    // retire tickets before it is drawn
+    let payer = ctx.payer.pubkey();
+    assert_givefi_error(send(&mut ctx, &[redraw_ix(payer)], &[]).await, GivefiError::SlotHashNotReady);
+    let claim = claim_prize_ix(1, loser, &entrants[loser as usize]);
+    assert_givefi_error(send(&mut ctx, &[claim], &[&entrants[loser as usize]]).await, GivefiError::RedrawInProgress);
+
+    let giveaway = redraw(&mut ctx).await;
+    let second_winner = giveaway.winners[0];
+    assert_ne!(second_winner, first_winner);
+    assert!(giveaway.pending_redraws.is_empty());
+    assert_eq!(giveaway.status, GiveawayStatus::Drawn);
+    let now = clock(&mut ctx).await.unix_timestamp;
+    assert_eq!(giveaway.winner_deadlines, vec![now + WINDOW_SECS]);
+
+    // One ticket is left, so the second redraw has to land on it
+    close_claim_window(&mut ctx).await;
+    let ix = reclaim_ix(&mut ctx, second_winner, None).await;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let giveaway = redraw(&mut ctx).await;
+    let third_winner = giveaway.winners[0];
+    assert_eq!(third_winner, 3 - first_winner - second_winner);
+    assert_eq!(giveaway.redraw_pool(), 0);
+
+    // Nothing is left to draw from: the share is routed to the creator and the giveaway settles
+    close_claim_window(&mut ctx).await;
+    let ix = reclaim_ix(&mut ctx, third_winner, None).await;
+    assert_givefi_error(send(&mut ctx, &[ix], &[]).await, GivefiError::InvalidUnclaimedRecipient);
+    let creator_before = lamports(&mut ctx, creator.pubkey()).await;
+    let ix = reclaim_ix(&mut ctx, third_winner, Some(creator.pubkey())).await;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    let share = tier_slice(3 * ENTRY_COST, &giveaway.prize_tiers[0]).unwrap();
+    assert_eq!(lamports(&mut ctx, creator.pubkey()).await - creator_before, share);
+    assert_eq!(giveaway.forfeited, 1);
+    assert_eq!(giveaway.sol_ledger.paid_out, share);
+    assert_eq!(giveaway.status, GiveawayStatus::Settled);
+}
+
+// Without redraws a loser may close its entry straight away, and an unclaimed share goes to the
+// policy's recipient once the window has passed
+#[tokio::test]
+async fn unclaimed_share_is_routed_to_the_treasury() {
+    let creator = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new()];
+    let treasury = Keypair::new().pubkey();
+    let mut wallets = vec![&creator];
+    wallets.extend(&entrants);
+    let mut ctx = program_test(&wallets).start_with_context().await;
+
+    initialize(&mut ctx, treasury).await;
+    set_oracle_program(&mut ctx, mock_oracle::ID).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    let giveaway = drawn_giveaway(&mut ctx, &creator, &entrants, args(now, UnclaimedPolicy::Treasury)).await;
+    let winner = giveaway.winners[0];
+    let loser = 1 - winner;
+
+    send(&mut ctx, &[close_losing_entry_ix(loser, &entrants[loser as usize])], &[&entrants[loser as usize]])
+        .await
+        .unwrap();
+
+    close_claim_window(&mut ctx).await;
+    let ix = reclaim_ix(&mut ctx, winner, Some(creator.pubkey())).await;
+    assert_givefi_error(send(&mut ctx, &[ix], &[]).await, GivefiError::InvalidUnclaimedRecipient);
+    let ix = reclaim_ix(&mut ctx, winner, Some(treasury)).await;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    let share = tier_slice(2 * ENTRY_COST, &giveaway.prize_tiers[0]).unwrap();
+    assert_eq!(lamports(&mut ctx, treasury).await, share);
+    assert_eq!(giveaway.sol_ledger.fees, share);
+    assert_eq!(giveaway.winners, vec![winner]);
+    assert!(giveaway.retired_tickets.is_empty());
+    assert_eq!(giveaway.forfeited, 1);
+    assert_eq!(giveaway.status, GiveawayStatus::Settled);
+    assert!(!account_exists(&mut ctx, entry_address(1, winner)).await);
+}
+
+// A redrawn winner's window runs from its redraw, while the other winners keep their own deadline.
+// A redraw slot whose hash aged out unread is re-pinned only by the platform.
+#[tokio::test]
+async fn redrawn_winner_gets_its_own_window() {
+    let creator = Keypair::new();
+    let outsider = Keypair::new();
+    let entrants = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let mut wallets = vec![&creator, &outsider];
+    wallets.extend(&entrants);
+    let mut ctx = program_test(&wallets).start_with_context().await;
+
+    initialize(&mut ctx, Keypair::new().pubkey()).await;
+    set_oracle_program(&mut ctx, mock_oracle::ID).await;
+    let now = clock(&mut ctx).await.unix_timestamp;
+    let mut create = args(now, UnclaimedPolicy::Redraw);
+    create.prize_tiers[0].count = 2;
+    let giveaway = drawn_giveaway(&mut ctx, &creator, &entrants, create).await;
+    let [forfeiter, kept] = [giveaway.winners[0], giveaway.winners[1]];
+    let kept_deadline = giveaway.winner_deadlines[1];
+
+    close_claim_window(&mut ctx).await;
+    let ix = reclaim_ix(&mut ctx, forfeiter, None).await;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    pass_slot(&mut ctx, giveaway.redraw_slot).await;
+    age_out_slot_hashes(&mut ctx).await;
+    let result = send(&mut ctx, &[redraw_ix(outsider.pubkey())], &[&outsider]).await;
+    assert_givefi_error(result, GivefiError::SlotHashUnavailable);
+    let giveaway = redraw(&mut ctx).await;
+    assert_eq!(giveaway.pending_redraws, vec![0]);
+    assert!(giveaway.redraw_slot > clock(&mut ctx).await.slot);
+
+    let giveaway = redraw(&mut ctx).await;
+    let replacement = giveaway.winners[0];
+    assert_eq!(replacement, 3 - forfeiter - kept);
+    assert_eq!(giveaway.winner_deadlines[1], kept_deadline);
+    assert!(giveaway.winner_deadlines[0] > kept_deadline);
+
+    // The kept winner's window is still the one it was drawn with
+    let creator_before = lamports(&mut ctx, creator.pubkey()).await;
+    let ix = reclaim_ix(&mut ctx, kept, Some(creator.pubkey())).await;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert!(lamports(&mut ctx, creator.pubkey()).await > creator_before);
+
+    let claim = claim_prize_ix(1, replacement, &entrants[replacement as usize]);
+    send(&mut ctx, &[claim], &[&entrants[replacement as usize]]).await.unwrap();
+    let giveaway: Giveaway = fetch(&mut ctx, giveaway_address(1)).await;
+    assert_eq!(giveaway.status, GiveawayStatus::Settled);
+}
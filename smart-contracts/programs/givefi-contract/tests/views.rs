@@ -0,0 +1,102 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::giveaway_fixture;
+use givefi_contract::{AcceptedMint, ClaimDeadline, Currency, GiveawayStatus, PrizeTier, UnclaimedPolicy, VaultLedger};
+
+fn tier(count: u8, share_bps: u16) -> PrizeTier {
+    PrizeTier { count, share_bps, description: String::new() }
+}
+
+#[test]
+fn seconds_remaining_only_counts_while_entries_are_open() {
+    let mut giveaway = giveaway_fixture(10, vec![tier(1, 10_000)]);
+    assert_eq!(giveaway.summary(400).seconds_remaining, 600);
+    assert_eq!(giveaway.summary(2_000).seconds_remaining, 0);
+
+    giveaway.status = GiveawayStatus::Drawn;
+    assert_eq!(giveaway.summary(400).seconds_remaining, 0);
+}
+
+#[test]
+fn pools_list_every_accepted_currency() {
+    let mint = Pubkey::new_unique();
+    let mut giveaway = giveaway_fixture(6, vec![tier(1, 10_000)]);
+    giveaway.sol_entries = 3;
+    giveaway.entry_cost_give = Some(50);
+    giveaway.give_entries = 2;
+    giveaway.give_pool = 99;
+    giveaway.accepted_mints.push(AcceptedMint { mint, price: 20, entries: 1, pool: 20, ledger: VaultLedger::default() });
+
+    let pools = giveaway.pools().unwrap();
+    assert_eq!(pools.len(), 3);
+    assert_eq!((pools[0].currency, pools[0].collected), (Currency::Sol, 3_000));
+    assert_eq!((pools[1].currency, pools[1].collected), (Currency::Give, 99));
+    assert_eq!((pools[2].currency, pools[2].tickets_sold), (Currency::Mint(mint), 1));
+}
+
+#[test]
+fn wallet_odds_follow_draws_without_replacement() {
+    let wallet = Pubkey::new_unique();
+    let single = giveaway_fixture(4, vec![tier(1, 10_000)]);
+    assert_eq!(single.wallet_odds(wallet, 0).win_chance_bps, 0);
+    assert_eq!(single.wallet_odds(wallet, 1).win_chance_bps, 2_500);
+    assert_eq!(single.wallet_odds(wallet, 4).win_chance_bps, 10_000);
+
+    // 1 ticket of 4 with 2 winners: 1 - (3/4 * 2/3) = 50%
+    let pair = giveaway_fixture(4, vec![tier(2, 10_000)]);
+    assert_eq!(pair.wallet_odds(wallet, 1).win_chance_bps, 5_000);
+    // Only one other ticket, so one of the two winners must be ours
+    assert_eq!(pair.wallet_odds(wallet, 3).win_chance_bps, 10_000);
+
+    let empty = giveaway_fixture(0, vec![tier(1, 10_000)]);
+    assert_eq!(empty.wallet_odds(wallet, 0).win_chance_bps, 0);
+}
+
+#[test]
+fn payout_breakdown_matches_jackpot_claims() {
+    let mut giveaway = giveaway_fixture(10, vec![tier(1, 7_000), tier(3, 3_000)]);
+    giveaway.prize_amount = 900;
+
+    let breakdown = giveaway.payout_breakdown().unwrap();
+    assert_eq!(breakdown.prize_per_winner_by_tier, vec![630, 90]);
+    assert_eq!(breakdown.winner_counts_by_tier, vec![1, 3]);
+
+    // 10_000 lamports: 7_000 for first place, 1_000 per runner-up
+    let sol = &breakdown.jackpot[0];
+    assert_eq!(sol.pool, 10_000);
+    assert_eq!(sol.per_winner_by_tier, vec![4_200, 600]);
+    assert_eq!(sol.winners + sol.creator + sol.treasury, 10_000);
+    assert_eq!(sol.creator, 3_300);
+
+    giveaway.commitment_slashed = true;
+    let sol = &giveaway.payout_breakdown().unwrap().jackpot[0];
+    assert_eq!((sol.creator, sol.treasury), (0, 4_000));
+
+    giveaway.jackpot_option_enabled = false;
+    assert!(giveaway.payout_breakdown().unwrap().jackpot.is_empty());
+}
+
+#[test]
+fn claim_window_runs_per_winner() {
+    let mut giveaway = giveaway_fixture(10, vec![tier(1, 7_000), tier(2, 3_000)]);
+    assert!(!giveaway.claim_window_closed(&[0], i64::MAX));
+    assert_eq!(giveaway.summary(0).claim_deadline, None);
+
+    giveaway.claim_deadline = Some(ClaimDeadline { window_secs: 600, policy: UnclaimedPolicy::Redraw });
+    giveaway.status = GiveawayStatus::Drawn;
+    giveaway.record_winners(vec![4, 2, 5], 1_000);
+    assert!(!giveaway.claim_window_closed(&[0], 1_599));
+    assert!(giveaway.claim_window_closed(&[0], 1_600));
+    assert_eq!(giveaway.summary(0).claim_deadline, Some(1_600));
+
+    // A redrawn runner-up gets a window of its own; an entry holding it stays claimable until then
+    giveaway.winner_deadlines[2] = 2_000;
+    assert!(giveaway.claim_window_closed(&[1], 1_600));
+    assert!(!giveaway.claim_window_closed(&[0, 2], 1_600));
+    assert_eq!(giveaway.summary(0).claim_deadline, Some(2_000));
+
+    // Entry #4 holds tickets 4..7: first place and one runner-up
+    assert_eq!(giveaway.winning_positions(4, 3).unwrap(), vec![0, 2]);
+    assert_eq!(giveaway.tiers_won(4, 3).unwrap(), vec![0, 1]);
+}
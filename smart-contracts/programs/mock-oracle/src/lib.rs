@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use givefi_contract::cpi::accounts::FulfillRandomness;
+use givefi_contract::program::Givefi;
+use givefi_contract::ORACLE_AUTHORITY_SEED;
+
+declare_id!("FHiwVsku7L79zPGRRMEbvUXNS2REWbfkA5Kj2ha5UujR");
+
+// Stand-in for a real VRF: whoever calls `fulfill` picks the result, and this program
+// signs it with its oracle authority PDA. Only for localnet and tests.
+#[program]
+pub mod mock_oracle {
+    use super::*;
+
+    pub fn fulfill(ctx: Context<Fulfill>, result: [u8; 32]) -> Result<()> {
+        let seeds: &[&[u8]] = &[ORACLE_AUTHORITY_SEED, &[ctx.bumps.oracle_authority]];
+        let signer = &[seeds];
+
+        let cpi_accounts = FulfillRandomness {
+            giveaway: ctx.accounts.giveaway.to_account_info(),
+            randomness_request: ctx.accounts.randomness_request.to_account_info(),
+            program_state: ctx.accounts.program_state.to_account_info(),
+            oracle_authority: ctx.accounts.oracle_authority.to_account_info(),
+            event_authority: ctx.accounts.givefi_event_authority.to_account_info(),
+            program: ctx.accounts.givefi_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.givefi_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        givefi_contract::cpi::fulfill_randomness(cpi_ctx, result)?;
+
+        msg!("Mock oracle fulfilled randomness");
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Fulfill<'info> {
+    /// CHECK: Forwarded to the givefi program, which validates it as the giveaway PDA.
+    #[account(mut)]
+    pub giveaway: UncheckedAccount<'info>,
+    /// CHECK: Forwarded to the givefi program, which validates it as the randomness request PDA.
+    #[account(mut)]
+    pub randomness_request: UncheckedAccount<'info>,
+    /// CHECK: Forwarded to the givefi program, which validates it as the program state PDA.
+    pub program_state: UncheckedAccount<'info>,
+    /// CHECK: PDA of this program used only as the CPI signer.
+    #[account(
+        seeds = [ORACLE_AUTHORITY_SEED],
+        bump
+    )]
+    pub oracle_authority: UncheckedAccount<'info>,
+    pub caller: Signer<'info>,
+    /// CHECK: The givefi program's event authority, which it checks when emitting events.
+    #[account(
+        seeds = [b"__event_authority"],
+        bump,
+        seeds::program = givefi_program.key()
+    )]
+    pub givefi_event_authority: UncheckedAccount<'info>,
+    pub givefi_program: Program<'info, Givefi>,
+}